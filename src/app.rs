@@ -4,19 +4,29 @@ use std::{
 		Arc, Mutex
 	},
 	thread,
-	time::{Duration, SystemTime}
+	time::{Duration, Instant}
 };
 
-use glam::{vec2, vec3};
-use winit::event_loop::EventLoopProxy;
+use winit::{event::VirtualKeyCode, event_loop::EventLoopProxy};
+
+use cell_life::{
+	particles::{DeathParticles, ParticleSystem},
+	sim::{
+		clock::{SimClock, Speed},
+		Simulation, Tick
+	}
+};
 
 use crate::{
-	particles::ParticleSystem,
+	cli::Args,
 	render::{
-		layers::{dots::DotsLayer, particles::ParticlesLayer},
+		layers::{
+			dots::DotsLayer, links::LinksLayer, obstacles::ObstaclesLayer,
+			particles::ParticlesLayer
+		},
 		Renderer
 	},
-	sim::{receptors::attract::AttractionReceptor, Simulation, Tick},
+	setup,
 	window::Window
 };
 
@@ -55,7 +65,9 @@ impl WindowThread {
 	) -> Self {
 		let window = Window::new(APP_NAME, |gl| {
 			let mut renderer = Renderer::new(gl);
-			renderer.push_layer(|ctx| DotsLayer::new(ctx, simulation));
+			renderer.push_layer(|ctx| DotsLayer::new(ctx, Arc::clone(&simulation)));
+			renderer.push_layer(|ctx| LinksLayer::new(ctx, Arc::clone(&simulation)));
+			renderer.push_layer(|ctx| ObstaclesLayer::new(ctx, simulation));
 			renderer.push_layer(|ctx| ParticlesLayer::new(ctx, particle_system));
 			renderer
 		});
@@ -66,6 +78,14 @@ impl WindowThread {
 		WindowThreadSyncHandle::new(self.window.proxy())
 	}
 
+	fn on_close<F: FnOnce() + 'static>(&mut self, handler: F) {
+		self.window.on_close(handler);
+	}
+
+	fn on_key_press<F: FnMut(VirtualKeyCode) -> Option<String> + 'static>(&mut self, handler: F) {
+		self.window.on_key_press(handler);
+	}
+
 	fn start_sync(self) {
 		self.window.run()
 	}
@@ -121,17 +141,35 @@ struct SimThread {
 impl SimThread {
 	fn new(
 		simulation: Arc<Mutex<Simulation>>,
-		particle_system: Arc<Mutex<ParticleSystem>>
+		particle_system: Arc<Mutex<ParticleSystem>>,
+		clock: Arc<Mutex<SimClock>>
 	) -> Self {
+		let mut death_particles = DeathParticles::new(&mut simulation.lock().unwrap());
 		let synced_thread = SyncedThread::new(move |recv| {
-			for dt in recv {
-				{
-					let mut sim_lock = simulation.lock().unwrap();
-					sim_lock.tick(dt);
-				}
-				{
-					let mut ps_lock = particle_system.lock().unwrap();
-					ps_lock.tick(dt);
+			while let Ok(dt) = recv.recv() {
+				// Fold in any frames that queued up while the last batch of steps was running, so a
+				// slow frame doesn't leave a growing backlog behind.
+				let dt = dt + recv.try_iter().sum::<f64>();
+				let (steps, unlimited, step_size) = {
+					let mut clock_lock = clock.lock().unwrap();
+					let steps = clock_lock.advance(dt);
+					(steps, clock_lock.is_unlimited(), clock_lock.step_size())
+				};
+				// Without a speed limit, keep stepping for the length of a frame before checking
+				// back in, so the renderer still gets to see every frame's worth of progress.
+				let start = Instant::now();
+				let mut step = 0;
+				while step < steps || (unlimited && start.elapsed() < FRAME_DURATION) {
+					{
+						let mut sim_lock = simulation.lock().unwrap();
+						sim_lock.tick(step_size);
+					}
+					{
+						let mut ps_lock = particle_system.lock().unwrap();
+						death_particles.spawn(&mut ps_lock);
+						ps_lock.tick(step_size);
+					}
+					step += 1;
 				}
 			}
 		});
@@ -167,11 +205,12 @@ impl TimingThread {
 
 	fn start(mut self) {
 		thread::spawn(move || {
-			let mut last_frame = SystemTime::now();
+			let mut last_frame = Instant::now();
 			loop {
-				if last_frame.elapsed().unwrap() >= FRAME_DURATION {
-					self.tick(last_frame.elapsed().unwrap().as_secs_f64());
-					last_frame = SystemTime::now();
+				let elapsed = last_frame.elapsed();
+				if elapsed >= FRAME_DURATION {
+					last_frame = Instant::now();
+					self.tick(elapsed.as_secs_f64());
 				}
 			}
 		});
@@ -184,6 +223,30 @@ impl TimingThread {
 	}
 }
 
+/// Returns the clock's new state, to be shown in the window, if the key changed it.
+fn control_clock(clock: &mut SimClock, key: VirtualKeyCode) -> Option<String> {
+	match key {
+		VirtualKeyCode::Space => clock.toggle_pause(),
+		VirtualKeyCode::Right => {
+			clock.pause();
+			clock.step(1);
+		}
+		VirtualKeyCode::Plus | VirtualKeyCode::Equals | VirtualKeyCode::NumpadAdd => clock.faster(),
+		VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => clock.slower(),
+		VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => clock.set_speed(Speed::Scaled(1.0)),
+		VirtualKeyCode::F => clock.set_speed(match clock.speed() {
+			Speed::Unlimited => Speed::Scaled(1.0),
+			Speed::Scaled(_) => Speed::Unlimited
+		}),
+		_ => return None
+	}
+	if clock.is_paused() {
+		Some("Paused".to_owned())
+	} else {
+		Some(format!("Running at {}", clock.speed()))
+	}
+}
+
 pub struct App {
 	timing_thread: TimingThread,
 	window_thread: WindowThread,
@@ -191,16 +254,35 @@ pub struct App {
 }
 
 impl App {
-	pub fn new() -> Self {
-		let particle_system = Arc::new(Mutex::new(ParticleSystem::new()));
-		let simulation = Arc::new(Mutex::new(Self::create_simulation(Arc::clone(
-			&particle_system
-		))));
+	pub fn new(args: &Args) -> Self {
+		let (simulation, particle_system) = setup::create_simulation(args);
+		let simulation = Arc::new(Mutex::new(simulation));
+		let particle_system = Arc::new(Mutex::new(particle_system));
 
 		let mut timing_thread = TimingThread::new();
-		let window_thread =
+		let mut window_thread =
 			WindowThread::new(Arc::clone(&simulation), Arc::clone(&particle_system));
-		let sim_thread = SimThread::new(simulation, particle_system);
+		if let Some(path) = args.save.clone() {
+			let simulation = Arc::clone(&simulation);
+			let particle_system = Arc::clone(&particle_system);
+			window_thread.on_close(move || {
+				setup::save_snapshot(
+					&path,
+					&simulation.lock().unwrap(),
+					&particle_system.lock().unwrap()
+				);
+			});
+		}
+		let clock = Arc::new(Mutex::new(SimClock::new(args.timestep_config())));
+		{
+			let clock = Arc::clone(&clock);
+			window_thread.on_key_press(move |key| control_clock(&mut clock.lock().unwrap(), key));
+		}
+		println!(
+			"Controls: space to pause, right arrow to step, +/- to change speed, 0 for real time, \
+			 F to run as fast as possible"
+		);
+		let sim_thread = SimThread::new(simulation, particle_system, clock);
 
 		timing_thread.add_handle(sim_thread.sync_handle());
 		timing_thread.add_handle(window_thread.sync_handle());
@@ -212,29 +294,6 @@ impl App {
 		}
 	}
 
-	fn create_simulation(particle_system: Arc<Mutex<ParticleSystem>>) -> Simulation {
-		let mut sim = Simulation::new(particle_system);
-		sim.add_cell(
-			10.0,
-			vec3(0.5, 0.5, 0.0),
-			vec2(0.0, 0.0),
-			vec![Box::new(AttractionReceptor::new(vec3(0.0, 60.0, 50.0)))]
-		);
-		sim.add_cell(
-			3.0,
-			vec3(0.2, 0.5, 1.0),
-			vec2(500.0, 10.0),
-			vec![Box::new(AttractionReceptor::new(vec3(10.0, 0.0, 0.0)))]
-		);
-		sim.add_cell(
-			5.0,
-			vec3(0.2, 0.5, 1.0),
-			vec2(-200.0, -100.0),
-			vec![Box::new(AttractionReceptor::new(vec3(50.0, 0.0, 0.0)))]
-		);
-		sim
-	}
-
 	pub fn start(self) {
 		self.timing_thread.start();
 		self.sim_thread.start();
@@ -0,0 +1,270 @@
+use std::{num::NonZeroUsize, path::PathBuf, thread};
+
+use clap::{ArgGroup, Parser, ValueEnum};
+use glam::Vec2;
+
+use cell_life::sim::{
+	bounds::WorldBounds,
+	clock::TimestepConfig,
+	events::EventKind,
+	stats::{StatsConfig, StatsFormat},
+	LongRangeMode
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LoggedEvent {
+	Birth,
+	Death,
+	Division,
+	Predation,
+	EnergySpent
+}
+
+impl LoggedEvent {
+	fn matches(self, kind: &EventKind) -> bool {
+		matches!(
+			(self, kind),
+			(Self::Birth, EventKind::Birth { .. })
+				| (Self::Death, EventKind::Death { .. })
+				| (Self::Division, EventKind::Division { .. })
+				| (Self::Predation, EventKind::Predation { .. })
+				| (Self::EnergySpent, EventKind::EnergySpent { .. })
+		)
+	}
+}
+
+#[derive(Debug, Parser)]
+#[command(version, about, group(ArgGroup::new("run_length").args(["ticks", "duration"])))]
+pub struct Args {
+	/// Scenario file describing the world and initial population. A small built-in scenario is used
+	/// if omitted.
+	#[arg(long, conflicts_with = "load")]
+	pub scenario: Option<PathBuf>,
+
+	/// Seed for the simulation's random number generator. Overrides the scenario's seed, and a
+	/// random seed is picked if neither is given.
+	#[arg(long)]
+	pub seed: Option<u64>,
+
+	/// Length of a single simulation step in seconds.
+	#[arg(long, default_value_t = TimestepConfig::default().step, value_parser = parse_timestep)]
+	pub timestep: f64,
+
+	/// Maximum number of simulation steps run per frame to catch up with real time.
+	#[arg(
+		long,
+		default_value_t = TimestepConfig::default().max_steps,
+		value_parser = clap::value_parser!(u32).range(1..)
+	)]
+	pub max_catch_up: u32,
+
+	/// Approximate long-range interactions with a Barnes-Hut quadtree using this opening angle.
+	#[arg(long, value_parser = parse_opening_angle)]
+	pub opening_angle: Option<f32>,
+
+	/// Number of worker threads used to update cells. Defaults to the number of available cores.
+	#[arg(long)]
+	pub threads: Option<usize>,
+
+	/// Boundaries of the world: "open", "rect:<width>x<height>", "circle:<radius>" or
+	/// "torus:<width>x<height>". Overrides the bounds set by the scenario.
+	#[arg(long, value_parser = parse_bounds)]
+	pub bounds: Option<WorldBounds>,
+
+	/// Write population statistics to this file, as JSON lines if it ends in ".jsonl" and as CSV
+	/// otherwise.
+	#[arg(long)]
+	pub stats: Option<PathBuf>,
+
+	/// Number of ticks between two statistics samples.
+	#[arg(long, default_value_t = StatsConfig::default().interval)]
+	pub stats_interval: u64,
+
+	/// Log simulation events to this file as JSON lines.
+	#[arg(long)]
+	pub events: Option<PathBuf>,
+
+	/// Comma-separated kinds of events written to the --events log. Predation and energy use are
+	/// reported for every cell they affect on every tick, so they are only logged if asked for.
+	#[arg(
+		long,
+		value_enum,
+		value_delimiter = ',',
+		default_values_t = [LoggedEvent::Birth, LoggedEvent::Death, LoggedEvent::Division],
+		requires = "events"
+	)]
+	pub event_kinds: Vec<LoggedEvent>,
+
+	/// Resume from a snapshot instead of creating a new simulation. The world settings stored in
+	/// the snapshot take precedence over --bounds and --opening-angle.
+	#[arg(long)]
+	pub load: Option<PathBuf>,
+
+	/// Save a snapshot to this file when the window is closed or the headless run ends, as JSON if
+	/// it ends in ".json" and in the binary format otherwise.
+	#[arg(long)]
+	pub save: Option<PathBuf>,
+
+	/// Run the simulation as fast as possible without opening a window. Requires --ticks or
+	/// --duration.
+	#[arg(long, requires = "run_length")]
+	pub headless: bool,
+
+	/// Number of ticks to run in headless mode.
+	#[arg(long, requires = "headless")]
+	pub ticks: Option<u64>,
+
+	/// Number of simulated seconds to run in headless mode.
+	#[arg(long, requires = "headless")]
+	pub duration: Option<f64>,
+
+	/// Additionally save a numbered snapshot next to the --save file every this many ticks.
+	#[arg(long, requires_all = ["headless", "save"])]
+	pub snapshot_interval: Option<u64>,
+
+	/// Write the lineage tree to this file at the end of a headless run, as GraphML if it ends in
+	/// ".graphml" and in Newick format otherwise.
+	#[arg(long, requires = "headless")]
+	pub lineage: Option<PathBuf>
+}
+
+impl Args {
+	pub fn timestep_config(&self) -> TimestepConfig {
+		TimestepConfig {
+			step: self.timestep,
+			max_steps: self.max_catch_up
+		}
+	}
+
+	/// Number of ticks a headless run lasts, if either --ticks or --duration was given.
+	pub fn run_length(&self) -> Option<u64> {
+		match (self.ticks, self.duration) {
+			(Some(ticks), _) => Some(ticks),
+			(None, Some(duration)) => Some((duration / self.timestep).ceil().max(0.0) as u64),
+			(None, None) => None
+		}
+	}
+
+	pub fn threads(&self) -> usize {
+		self.threads.unwrap_or_else(|| {
+			thread::available_parallelism()
+				.map(NonZeroUsize::get)
+				.unwrap_or(1)
+		})
+	}
+
+	pub fn stats_config(&self) -> StatsConfig {
+		StatsConfig {
+			interval: self.stats_interval,
+			..Default::default()
+		}
+	}
+
+	pub fn stats_format(&self) -> StatsFormat {
+		let extension = self
+			.stats
+			.as_ref()
+			.and_then(|path| path.extension())
+			.and_then(|extension| extension.to_str());
+		match extension {
+			Some("jsonl") => StatsFormat::Jsonl,
+			_ => StatsFormat::Csv
+		}
+	}
+
+	pub fn event_filter(&self) -> impl Fn(&EventKind) -> bool + Send + 'static {
+		let kinds = self.event_kinds.clone();
+		move |kind| kinds.iter().any(|logged| logged.matches(kind))
+	}
+
+	pub fn long_range_mode(&self) -> Option<LongRangeMode> {
+		self.opening_angle
+			.map(|opening_angle| LongRangeMode::BarnesHut { opening_angle })
+	}
+}
+
+fn parse_opening_angle(value: &str) -> Result<f32, String> {
+	value
+		.trim()
+		.parse::<f32>()
+		.ok()
+		.filter(|angle| angle.is_finite() && *angle >= 0.0)
+		.ok_or_else(|| format!("Opening angle must be a non-negative number, got \"{value}\""))
+}
+
+fn parse_bounds(value: &str) -> Result<WorldBounds, String> {
+	let (kind, dimensions) = value.split_once(':').unwrap_or((value, ""));
+	let parse_number = |number: &str| {
+		number
+			.trim()
+			.parse::<f32>()
+			.map_err(|_| format!("Invalid world dimension \"{number}\""))
+	};
+	let parse_size = |size: &str| {
+		let (width, height) = size
+			.split_once('x')
+			.ok_or_else(|| format!("Expected <width>x<height>, got \"{size}\""))?;
+		Ok::<_, String>(Vec2::new(parse_number(width)?, parse_number(height)?))
+	};
+	let bounds = match kind {
+		"open" => Ok(WorldBounds::Open),
+		"rect" => WorldBounds::rectangle(parse_size(dimensions)?),
+		"circle" => WorldBounds::circle(parse_number(dimensions)?),
+		"torus" => WorldBounds::torus(parse_size(dimensions)?),
+		_ => return Err(format!("Unknown world bounds \"{kind}\""))
+	};
+	bounds.map_err(|err| err.to_string())
+}
+
+fn parse_timestep(value: &str) -> Result<f64, String> {
+	value
+		.trim()
+		.parse::<f64>()
+		.ok()
+		.filter(|step| step.is_finite() && *step > 0.0)
+		.ok_or_else(|| format!("Timestep must be a positive number of seconds, got \"{value}\""))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bounds_are_parsed() {
+		assert_eq!(parse_bounds("open"), Ok(WorldBounds::Open));
+		assert_eq!(
+			parse_bounds("rect:800x600"),
+			Ok(WorldBounds::Rectangle {
+				size: Vec2::new(800.0, 600.0)
+			})
+		);
+		assert_eq!(
+			parse_bounds("circle:500"),
+			Ok(WorldBounds::Circle { radius: 500.0 })
+		);
+		assert_eq!(
+			parse_bounds("torus: 1000 x 1500.5"),
+			Ok(WorldBounds::Torus {
+				size: Vec2::new(1000.0, 1500.5)
+			})
+		);
+	}
+
+	#[test]
+	fn invalid_bounds_are_rejected() {
+		for value in [
+			"",
+			"square:100",
+			"rect",
+			"rect:800",
+			"rect:800x",
+			"rect:0x600",
+			"circle:-5",
+			"circle:NaN",
+			"torus:infx100",
+			"torus:100x100x100"
+		] {
+			assert!(parse_bounds(value).is_err(), "{value:?} was accepted");
+		}
+	}
+}
@@ -1,19 +1,26 @@
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug)]
 pub struct Ecs<C> {
-	entity_components: Vec<Vec<C>>
+	entity_components: Vec<Vec<C>>,
+	free_entities: Vec<Entity>
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub struct Entity(usize);
 
 impl<C> Ecs<C> {
 	pub fn new() -> Self {
 		Self {
-			entity_components: Vec::new()
+			entity_components: Vec::new(),
+			free_entities: Vec::new()
 		}
 	}
 
 	pub fn entity(&mut self) -> Entity {
+		if let Some(entity) = self.free_entities.pop() {
+			return entity;
+		}
 		let id = self.entity_components.len();
 		self.entity_components.push(Vec::new());
 		Entity(id)
@@ -29,4 +36,18 @@ impl<C> Ecs<C> {
 	pub fn components(&self, entity: Entity) -> &[C] {
 		&self.entity_components[entity.0]
 	}
+
+	pub fn remove_entity(&mut self, entity: Entity) {
+		if entity.0 >= self.entity_components.len() {
+			panic!("Entity does not exist");
+		}
+		self.entity_components[entity.0].clear();
+		self.free_entities.push(entity);
+	}
+}
+
+impl<C> Default for Ecs<C> {
+	fn default() -> Self {
+		Self::new()
+	}
 }
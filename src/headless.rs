@@ -0,0 +1,151 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::mpsc::Receiver,
+	time::Instant
+};
+
+use cell_life::{
+	particles::{DeathParticles, ParticleSystem},
+	sim::{
+		events::{Event, EventKind},
+		stats::Sample,
+		Simulation, Tick
+	}
+};
+
+use crate::{cli::Args, setup};
+
+#[derive(Debug, Default)]
+struct EventCounts {
+	births: u64,
+	deaths: u64,
+	divisions: u64
+}
+
+impl EventCounts {
+	fn count(&mut self, events: &Receiver<Event>) {
+		for event in events.try_iter() {
+			match event.kind {
+				EventKind::Birth { .. } => self.births += 1,
+				EventKind::Death { .. } => self.deaths += 1,
+				EventKind::Division { .. } => self.divisions += 1,
+				_ => ()
+			}
+		}
+	}
+}
+
+pub struct HeadlessRunner {
+	simulation: Simulation,
+	particle_system: ParticleSystem,
+	death_particles: DeathParticles,
+	events: Receiver<Event>,
+	counts: EventCounts
+}
+
+impl HeadlessRunner {
+	pub fn new(args: &Args) -> Self {
+		let (mut simulation, particle_system) = setup::create_simulation(args);
+		let death_particles = DeathParticles::new(&mut simulation);
+		let events = simulation.subscribe(|kind| {
+			matches!(
+				kind,
+				EventKind::Birth { .. } | EventKind::Death { .. } | EventKind::Division { .. }
+			)
+		});
+		Self {
+			simulation,
+			particle_system,
+			death_particles,
+			events,
+			counts: EventCounts::default()
+		}
+	}
+
+	pub fn run(mut self, args: &Args) {
+		let ticks = args
+			.run_length()
+			.expect("Headless runs need a number of ticks or a duration");
+		let dt = args.timestep_config().step;
+		let start = Instant::now();
+		for _ in 0..ticks {
+			self.simulation.tick(dt);
+			self.death_particles.spawn(&mut self.particle_system);
+			self.particle_system.tick(dt);
+			self.counts.count(&self.events);
+
+			if let (Some(path), Some(interval)) = (&args.save, args.snapshot_interval) {
+				let tick = self.simulation.tick_count();
+				if interval > 0 && tick.is_multiple_of(interval) {
+					setup::save_snapshot(
+						&numbered_path(path, tick),
+						&self.simulation,
+						&self.particle_system
+					);
+				}
+			}
+		}
+		let elapsed = start.elapsed().as_secs_f64();
+
+		if let Some(path) = &args.save {
+			setup::save_snapshot(path, &self.simulation, &self.particle_system);
+		}
+		if let Some(path) = &args.lineage {
+			self.write_lineage(path);
+		}
+		self.print_summary(ticks, ticks as f64 * dt, elapsed);
+	}
+
+	fn write_lineage(&self, path: &Path) {
+		let lineage = self.simulation.lineage();
+		let output = match path.extension().and_then(|extension| extension.to_str()) {
+			Some("graphml") => lineage.to_graphml(),
+			_ => lineage.to_newick(self.simulation.tick_count())
+		};
+		match fs::write(path, output) {
+			Ok(()) => println!("Wrote lineage to {}", path.display()),
+			Err(err) => eprintln!("Failed to write lineage {}: {err}", path.display())
+		}
+	}
+
+	fn print_summary(&self, ticks: u64, sim_time: f64, elapsed: f64) {
+		let sample = Sample::collect(self.simulation.tick_count(), self.simulation.cells());
+		let max_generation = self
+			.simulation
+			.cells()
+			.map(|cell| cell.generation)
+			.max()
+			.unwrap_or(0);
+		println!(
+			"Ran {ticks} ticks ({sim_time:.1} s simulated) in {elapsed:.2} s ({:.0} ticks/s)",
+			ticks as f64 / elapsed.max(f64::EPSILON)
+		);
+		println!(
+			"Population: {} cells, {} births, {} deaths, {} divisions",
+			sample.population, self.counts.births, self.counts.deaths, self.counts.divisions
+		);
+		println!("Highest generation: {max_generation}");
+		println!(
+			"Energy: {:.1} total, {:.2} per cell",
+			sample.total_energy, sample.mean_energy
+		);
+		println!(
+			"Health: {:.2} per cell, mean speed {:.1}",
+			sample.mean_health, sample.mean_speed
+		);
+	}
+}
+
+/// Inserts the tick into the file name, e.g. "run.snap" becomes "run-1200.snap".
+fn numbered_path(path: &Path, tick: u64) -> PathBuf {
+	let stem = path
+		.file_stem()
+		.map(|stem| stem.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	let file_name = match path.extension() {
+		Some(extension) => format!("{stem}-{tick}.{}", extension.to_string_lossy()),
+		None => format!("{stem}-{tick}")
+	};
+	path.with_file_name(file_name)
+}
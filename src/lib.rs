@@ -0,0 +1,4 @@
+pub mod ecs;
+pub mod particles;
+pub mod rand;
+pub mod sim;
@@ -1,16 +1,33 @@
 #![cfg_attr(release, windows_subsystem = "windows")]
 #![feature(duration_consts_float)]
 
+#[cfg(feature = "gui")]
 use app::App;
+use clap::Parser;
+use cli::Args;
+use headless::HeadlessRunner;
+
+#[cfg(feature = "gui")]
 mod app;
-mod ecs;
-mod particles;
-mod rand;
+mod cli;
+mod headless;
+#[cfg(feature = "gui")]
 mod render;
-mod sim;
+mod setup;
+#[cfg(feature = "gui")]
 mod window;
 
 fn main() {
-	let app = App::new();
-	app.start();
+	let args = Args::parse();
+	if args.headless {
+		HeadlessRunner::new(&args).run(&args);
+		return;
+	}
+	#[cfg(feature = "gui")]
+	App::new(&args).start();
+	#[cfg(not(feature = "gui"))]
+	{
+		eprintln!("This build has no GUI support, run it with --headless");
+		std::process::exit(2);
+	}
 }
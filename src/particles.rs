@@ -1,38 +1,43 @@
-use std::time::SystemTime;
+use std::sync::mpsc::Receiver;
 
 use glam::{Vec2, Vec3};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-	rand::{rand, rand_point_in_circle, rand_with_spread},
-	render::{layers, ObjectProvider}
+	rand::Rng,
+	sim::{
+		events::{Event, EventKind},
+		Simulation
+	}
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct ParticleState {
 	position: Vec2,
 	rotation: f32
 }
 
-#[derive(Debug)]
-struct Particle {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Particle {
 	shape: Vec<Vec2>,
 	velocity: Vec2,
 	angular_velocity: f32,
 	lifetime: f32,
 	opacity: f32,
-	birthtime: SystemTime,
+	age: f32,
 	state: ParticleState
 }
 
-#[derive(Debug)]
-struct ParticleGroup {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleGroup {
 	color: Vec3,
 	particles: Vec<Particle>
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParticleSystem {
-	groups: Vec<ParticleGroup>
+	groups: Vec<ParticleGroup>,
+	rng: Rng
 }
 
 pub struct GroupSpawnProps {
@@ -50,9 +55,47 @@ const OPACITY_SPREAD: f32 = 0.15;
 const MAX_ANGULAR_VELOCITY: f32 = 1.0;
 const LIFETIME_SPREAD: f32 = 0.3;
 
+impl Particle {
+	pub fn shape(&self) -> &[Vec2] {
+		&self.shape
+	}
+
+	pub fn position(&self) -> Vec2 {
+		self.state.position
+	}
+
+	pub fn rotation(&self) -> f32 {
+		self.state.rotation
+	}
+
+	/// The particle's opacity, fading out towards the end of its lifetime.
+	pub fn opacity(&self) -> f32 {
+		self.opacity * f32::max(self.lifetime - self.age, 0.0)
+	}
+}
+
+impl ParticleGroup {
+	pub fn color(&self) -> Vec3 {
+		self.color
+	}
+
+	pub fn particles(&self) -> &[Particle] {
+		&self.particles
+	}
+}
+
 impl ParticleSystem {
-	pub fn new() -> Self {
-		Self { groups: Vec::new() }
+	/// Particles draw from their own generator, which is saved along with them, so that replays
+	/// and restored snapshots spawn exactly the same particles.
+	pub fn new(seed: u64) -> Self {
+		Self {
+			groups: Vec::new(),
+			rng: Rng::new(seed)
+		}
+	}
+
+	pub fn groups(&self) -> &[ParticleGroup] {
+		&self.groups
 	}
 
 	pub fn spawn_particle_group(
@@ -68,12 +111,13 @@ impl ParticleSystem {
 			opacity
 		}: GroupSpawnProps
 	) {
-		let offs_positions = Self::generate_points_in_radius(spread, count);
+		let rng = &mut self.rng;
+		let offs_positions = Self::generate_points_in_radius(rng, spread, count);
 		let particles: Vec<Particle> = offs_positions
 			.into_iter()
 			.map(|offs_pos| {
 				let vel = velocity * offs_pos / spread;
-				Self::generate_particle(position + offs_pos, vel, lifetime, size, opacity)
+				Self::generate_particle(rng, position + offs_pos, vel, lifetime, size, opacity)
 			})
 			.collect();
 		let group = ParticleGroup { color, particles };
@@ -85,26 +129,27 @@ impl ParticleSystem {
 			for particle in &mut group.particles {
 				particle.state.position += particle.velocity * dt as f32;
 				particle.state.rotation += particle.angular_velocity * dt as f32;
+				particle.age += dt as f32;
 			}
 			Self::remove_dead_particles(group)
 		}
+		self.groups.retain(|group| !group.particles.is_empty());
 	}
 
 	fn remove_dead_particles(group: &mut ParticleGroup) {
-		group
-			.particles
-			.retain(|p| p.birthtime.elapsed().unwrap().as_secs_f32() < p.lifetime);
+		group.particles.retain(|p| p.age < p.lifetime);
 	}
 
-	fn generate_points_in_radius(radius: f32, num_points: usize) -> Vec<Vec2> {
+	fn generate_points_in_radius(rng: &mut Rng, radius: f32, num_points: usize) -> Vec<Vec2> {
 		let mut points = Vec::with_capacity(num_points);
 		for _ in 0..num_points {
-			points.push(rand_point_in_circle(radius))
+			points.push(rng.rand_point_in_circle(radius))
 		}
 		points
 	}
 
 	fn generate_particle(
+		rng: &mut Rng,
 		position: Vec2,
 		velocity: Vec2,
 		base_lifetime: f32,
@@ -112,12 +157,12 @@ impl ParticleSystem {
 		opacity: f32
 	) -> Particle {
 		Particle {
-			shape: Self::generate_points_in_radius(size, 3),
+			shape: Self::generate_points_in_radius(rng, size, 3),
 			velocity,
-			angular_velocity: rand() * MAX_ANGULAR_VELOCITY,
-			lifetime: rand_with_spread(base_lifetime, LIFETIME_SPREAD),
-			birthtime: SystemTime::now(),
-			opacity: rand_with_spread(opacity, OPACITY_SPREAD),
+			angular_velocity: rng.rand() * MAX_ANGULAR_VELOCITY,
+			lifetime: rng.rand_with_spread(base_lifetime, LIFETIME_SPREAD),
+			age: 0.0,
+			opacity: rng.rand_with_spread(opacity, OPACITY_SPREAD),
 			state: ParticleState {
 				position,
 				rotation: 0.0
@@ -126,25 +171,115 @@ impl ParticleSystem {
 	}
 }
 
-impl ObjectProvider<layers::particles::ParticleGroup> for ParticleSystem {
-	fn iter_objects(&self) -> Box<dyn Iterator<Item = layers::particles::ParticleGroup> + '_> {
-		Box::new(self.groups.iter().map(|group| {
-			layers::particles::ParticleGroup::new(
-				group.color,
-				group
-					.particles
-					.iter()
-					.map(|particle| {
-						let time_since_birth = particle.birthtime.elapsed().unwrap().as_secs_f32();
-						layers::particles::Particle::new(
-							particle.opacity * f32::max(particle.lifetime - time_since_birth, 0.0),
-							particle.state.position,
-							particle.state.rotation,
-							particle.shape.clone()
-						)
-					})
-					.collect()
-			)
-		}))
+pub struct DeathParticles {
+	deaths: Receiver<Event>
+}
+
+impl DeathParticles {
+	pub fn new(simulation: &mut Simulation) -> Self {
+		let deaths = simulation.subscribe(|kind| matches!(kind, EventKind::Death { .. }));
+		Self { deaths }
+	}
+
+	pub fn spawn(&mut self, particle_system: &mut ParticleSystem) {
+		for event in self.deaths.try_iter() {
+			let EventKind::Death {
+				position,
+				color,
+				size,
+				..
+			} = event.kind
+			else {
+				continue;
+			};
+			particle_system.spawn_particle_group(GroupSpawnProps {
+				color,
+				count: 30,
+				position,
+				velocity: 50.0,
+				lifetime: 1.0,
+				spread: size,
+				size: 15.0,
+				opacity: 0.2
+			});
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sim::clock::{SimClock, Speed, TimestepConfig};
+
+	const CONFIG: TimestepConfig = TimestepConfig {
+		step: 0.125,
+		max_steps: 64
+	};
+
+	fn particle_system() -> ParticleSystem {
+		let mut particle_system = ParticleSystem::new(1);
+		particle_system.spawn_particle_group(GroupSpawnProps {
+			color: Vec3::ONE,
+			count: 10,
+			position: Vec2::ZERO,
+			velocity: 50.0,
+			lifetime: 1.0,
+			spread: 5.0,
+			size: 15.0,
+			opacity: 0.2
+		});
+		particle_system
+	}
+
+	/// Runs the steps the clock hands out for `dt` of real time, as the simulation thread does.
+	fn advance(particle_system: &mut ParticleSystem, clock: &mut SimClock, dt: f64) {
+		for _ in 0..clock.advance(dt) {
+			particle_system.tick(clock.step_size());
+		}
+	}
+
+	fn ages(particle_system: &ParticleSystem) -> Vec<f32> {
+		particle_system
+			.groups()
+			.iter()
+			.flat_map(|group| group.particles())
+			.map(|particle| particle.age)
+			.collect()
+	}
+
+	#[test]
+	fn particles_age_by_simulated_time() {
+		let mut particle_system = particle_system();
+		let mut clock = SimClock::new(CONFIG);
+		clock.set_speed(Speed::Scaled(2.0));
+		advance(&mut particle_system, &mut clock, 0.25);
+		assert_eq!(ages(&particle_system), vec![0.5; 10]);
+	}
+
+	#[test]
+	fn particles_expire_after_their_lifetime() {
+		// Lifetimes are spread around 1 by at most 0.15 either way.
+		let mut particle_system = particle_system();
+		let mut clock = SimClock::new(CONFIG);
+		advance(&mut particle_system, &mut clock, 0.75);
+		assert_eq!(ages(&particle_system).len(), 10);
+		assert!(particle_system.groups()[0]
+			.particles()
+			.iter()
+			.all(|particle| particle.opacity() > 0.0));
+		advance(&mut particle_system, &mut clock, 0.5);
+		assert!(particle_system.groups().is_empty());
+	}
+
+	#[test]
+	fn paused_particles_do_not_age() {
+		let mut particle_system = particle_system();
+		let mut clock = SimClock::new(CONFIG);
+		clock.pause();
+		advance(&mut particle_system, &mut clock, 1.0);
+		assert_eq!(ages(&particle_system), vec![0.0; 10]);
+		clock.step(1);
+		advance(&mut particle_system, &mut clock, 1.0);
+		assert_eq!(ages(&particle_system), vec![0.125; 10]);
 	}
 }
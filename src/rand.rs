@@ -1,25 +1,63 @@
 use std::f32::consts::TAU;
 
 use glam::Vec2;
-use rand::random;
+use rand::{Rng as _, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use serde::{Deserialize, Serialize};
+use uuid::{Builder, Uuid};
 
-#[inline]
-pub fn rand() -> f32 {
-	random::<f32>()
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rng {
+	inner: Pcg64Mcg
 }
 
-#[inline]
-pub fn rand_in_range(min: f32, max: f32) -> f32 {
-	min + random::<f32>() * (max - min)
-}
+impl Rng {
+	pub fn new(seed: u64) -> Self {
+		Self {
+			inner: Pcg64Mcg::seed_from_u64(seed)
+		}
+	}
+
+	#[inline]
+	pub fn rand(&mut self) -> f32 {
+		self.inner.gen::<f32>()
+	}
+
+	#[inline]
+	pub fn rand_u64(&mut self) -> u64 {
+		self.inner.gen::<u64>()
+	}
+
+	#[inline]
+	pub fn rand_in_range(&mut self, min: f32, max: f32) -> f32 {
+		min + self.rand() * (max - min)
+	}
+
+	#[inline]
+	pub fn rand_index(&mut self, len: usize) -> usize {
+		self.inner.gen_range(0..len)
+	}
+
+	pub fn rand_with_spread(&mut self, avg: f32, spread: f32) -> f32 {
+		let half_spread = spread / 2.0;
+		self.rand_in_range(avg - half_spread, avg + half_spread)
+	}
+
+	pub fn rand_direction(&mut self) -> Vec2 {
+		Vec2::from_angle(self.rand() * TAU)
+	}
+
+	pub fn rand_point_in_circle(&mut self, radius: f32) -> Vec2 {
+		let abs = (self.rand() * radius).sqrt();
+		let angle = self.rand() * TAU;
+		abs * Vec2::from_angle(angle)
+	}
 
-pub fn rand_with_spread(avg: f32, spread: f32) -> f32 {
-	let half_spread = spread / 2.0;
-	rand_in_range(avg - half_spread, avg + half_spread)
+	pub fn uuid(&mut self) -> Uuid {
+		Builder::from_random_bytes(self.inner.gen()).into_uuid()
+	}
 }
 
-pub fn rand_point_in_circle(radius: f32) -> Vec2 {
-	let abs = (rand() * radius).sqrt();
-	let angle = rand() * TAU;
-	abs * Vec2::from_angle(angle)
+pub fn random_seed() -> u64 {
+	rand::random()
 }
@@ -1,5 +1,6 @@
 use std::sync::{Arc, Mutex};
 
+use cell_life::sim::{cell::Cell, Simulation};
 use crevice::std430::{self, AsStd430};
 use glam::{vec2, Vec2, Vec3};
 use winit::dpi::LogicalSize;
@@ -122,3 +123,21 @@ impl<P: ObjectProvider<Dot>> Layer for DotsLayer<P> {
 		self.ctx.draw(glow::TRIANGLE_STRIP, NUM_VERTICES);
 	}
 }
+
+impl From<&Cell> for Dot {
+	fn from(value: &Cell) -> Self {
+		Dot {
+			coords: value.position,
+			radius: value.size,
+			color: value.color,
+			brightness: value.energy as f32
+		}
+	}
+}
+
+impl ObjectProvider<Dot> for Simulation {
+	fn iter_objects(&self) -> Box<dyn Iterator<Item = Dot> + '_> {
+		let iter = self.cells().map(|cell| cell.into());
+		Box::new(iter)
+	}
+}
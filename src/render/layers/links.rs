@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex};
+
+use cell_life::sim::Simulation;
+use glam::{vec2, Vec2, Vec3};
+use winit::dpi::LogicalSize;
+
+use crate::render::{
+	buffer::Buffer, renderer::Layer, shader::ShaderProgram, vertex_model::VertexModel,
+	GraphicsContext, ObjectProvider
+};
+
+#[derive(Debug)]
+pub struct Link {
+	pub start: Vec2,
+	pub end: Vec2,
+	pub start_color: Vec3,
+	pub end_color: Vec3
+}
+
+#[allow(unused)]
+#[derive(Debug)]
+struct Vertex {
+	position: Vec2,
+	color: Vec3
+}
+
+pub struct LinksLayer<P: ObjectProvider<Link>> {
+	ctx: GraphicsContext,
+	vertex_model: VertexModel,
+	vertex_buffer: Buffer,
+	shader_program: ShaderProgram,
+	link_provider: Arc<Mutex<P>>
+}
+
+const VERTEX_SHADER: &str = include_str!("./shaders/links.vert.glsl");
+const FRAGMENT_SHADER: &str = include_str!("./shaders/links.frag.glsl");
+
+impl<P: ObjectProvider<Link>> LinksLayer<P> {
+	pub fn new(ctx: GraphicsContext, link_provider: Arc<Mutex<P>>) -> Self {
+		let mut vertex_model = ctx.make_vertex_model();
+		vertex_model.add_attribute(2, glow::FLOAT);
+		vertex_model.add_attribute(3, glow::FLOAT);
+
+		let vertex_buffer = ctx.make_buffer(glow::ARRAY_BUFFER);
+		vertex_buffer.bind();
+		vertex_model.apply();
+
+		let shader_program = ctx.make_program(vec![
+			ctx.make_shader(glow::VERTEX_SHADER, VERTEX_SHADER),
+			ctx.make_shader(glow::FRAGMENT_SHADER, FRAGMENT_SHADER),
+		]);
+
+		Self {
+			ctx,
+			vertex_model,
+			vertex_buffer,
+			shader_program,
+			link_provider
+		}
+	}
+
+	fn write_vertices(&mut self, size: LogicalSize<f32>) -> usize {
+		let links: Vec<Link> = {
+			let link_provider = self.link_provider.lock().unwrap();
+			link_provider.iter_objects().collect()
+		};
+
+		let scale = 2.0 / vec2(size.width, size.height);
+		let vertices: Vec<Vertex> = links
+			.iter()
+			.flat_map(|link| {
+				[
+					Vertex {
+						position: link.start * scale,
+						color: link.start_color
+					},
+					Vertex {
+						position: link.end * scale,
+						color: link.end_color
+					}
+				]
+			})
+			.collect();
+		self.vertex_buffer.set_data(&vertices, glow::STREAM_DRAW);
+		vertices.len()
+	}
+}
+
+impl<P: ObjectProvider<Link>> Layer for LinksLayer<P> {
+	fn draw(&mut self, size: LogicalSize<f32>) {
+		self.vertex_model.bind();
+		self.vertex_buffer.bind();
+		self.shader_program.activate();
+
+		let num_vertices = self.write_vertices(size);
+		if num_vertices > 0 {
+			self.ctx.draw(glow::LINES, num_vertices);
+		}
+	}
+}
+
+impl ObjectProvider<Link> for Simulation {
+	fn iter_objects(&self) -> Box<dyn Iterator<Item = Link> + '_> {
+		let iter = self.bonds().iter().filter_map(|(a, b, _)| {
+			let (a, b) = (self.cell(a)?, self.cell(b)?);
+			Some(Link {
+				start: a.position,
+				end: a.position + self.bounds().displacement(a.position, b.position),
+				start_color: a.color,
+				end_color: b.color
+			})
+		});
+		Box::new(iter)
+	}
+}
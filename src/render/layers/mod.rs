@@ -1,2 +1,4 @@
 pub mod dots;
+pub mod links;
+pub mod obstacles;
 pub mod particles;
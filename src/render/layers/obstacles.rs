@@ -0,0 +1,153 @@
+use std::sync::{Arc, Mutex};
+
+use cell_life::sim::{obstacles::Obstacle, Simulation};
+use glam::{vec2, Vec2};
+use winit::dpi::LogicalSize;
+
+use crate::render::{
+	buffer::Buffer, renderer::Layer, shader::ShaderProgram, vertex_model::VertexModel,
+	GraphicsContext, ObjectProvider
+};
+
+#[derive(Debug)]
+pub struct Polygon {
+	pub vertices: Vec<Vec2>
+}
+
+impl Polygon {
+	fn signed_area(&self) -> f32 {
+		let vertices = &self.vertices;
+		(0..vertices.len())
+			.map(|i| vertices[i].perp_dot(vertices[(i + 1) % vertices.len()]))
+			.sum::<f32>()
+			/ 2.0
+	}
+
+	fn triangulate(&self) -> Vec<Vec2> {
+		let mut remaining = self.vertices.clone();
+		if self.signed_area() < 0.0 {
+			remaining.reverse();
+		}
+
+		// Ear clipping: repeatedly cut off a convex corner that contains no other vertex.
+		let mut triangles = Vec::new();
+		while remaining.len() > 3 {
+			let len = remaining.len();
+			let ear = (0..len).find(|&i| {
+				let (a, b, c) = (
+					remaining[(i + len - 1) % len],
+					remaining[i],
+					remaining[(i + 1) % len]
+				);
+				(b - a).perp_dot(c - b) > 0.0
+					&& remaining
+						.iter()
+						.all(|&p| p == a || p == b || p == c || !in_triangle(p, a, b, c))
+			});
+			// Degenerate outlines have no ear left; drop a vertex so the loop still terminates.
+			let ear = ear.unwrap_or(0);
+			triangles.extend([
+				remaining[(ear + len - 1) % len],
+				remaining[ear],
+				remaining[(ear + 1) % len]
+			]);
+			remaining.remove(ear);
+		}
+		if remaining.len() == 3 {
+			triangles.extend(remaining);
+		}
+		triangles
+	}
+}
+
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+	(b - a).perp_dot(p - a) >= 0.0
+		&& (c - b).perp_dot(p - b) >= 0.0
+		&& (a - c).perp_dot(p - c) >= 0.0
+}
+
+#[allow(unused)]
+#[derive(Debug)]
+struct Vertex {
+	position: Vec2
+}
+
+pub struct ObstaclesLayer<P: ObjectProvider<Polygon>> {
+	ctx: GraphicsContext,
+	vertex_model: VertexModel,
+	vertex_buffer: Buffer,
+	shader_program: ShaderProgram,
+	polygon_provider: Arc<Mutex<P>>
+}
+
+const VERTEX_SHADER: &str = include_str!("./shaders/obstacles.vert.glsl");
+const FRAGMENT_SHADER: &str = include_str!("./shaders/obstacles.frag.glsl");
+
+impl<P: ObjectProvider<Polygon>> ObstaclesLayer<P> {
+	pub fn new(ctx: GraphicsContext, polygon_provider: Arc<Mutex<P>>) -> Self {
+		let mut vertex_model = ctx.make_vertex_model();
+		vertex_model.add_attribute(2, glow::FLOAT);
+
+		let vertex_buffer = ctx.make_buffer(glow::ARRAY_BUFFER);
+		vertex_buffer.bind();
+		vertex_model.apply();
+
+		let shader_program = ctx.make_program(vec![
+			ctx.make_shader(glow::VERTEX_SHADER, VERTEX_SHADER),
+			ctx.make_shader(glow::FRAGMENT_SHADER, FRAGMENT_SHADER),
+		]);
+
+		Self {
+			ctx,
+			vertex_model,
+			vertex_buffer,
+			shader_program,
+			polygon_provider
+		}
+	}
+
+	fn write_vertices(&mut self, size: LogicalSize<f32>) -> usize {
+		let polygons: Vec<Polygon> = {
+			let polygon_provider = self.polygon_provider.lock().unwrap();
+			polygon_provider.iter_objects().collect()
+		};
+
+		let vertices: Vec<Vertex> = polygons
+			.iter()
+			.flat_map(|polygon| polygon.triangulate())
+			.map(|point| Vertex {
+				position: 2.0 * point / vec2(size.width, size.height)
+			})
+			.collect();
+		self.vertex_buffer.set_data(&vertices, glow::STREAM_DRAW);
+		vertices.len()
+	}
+}
+
+impl<P: ObjectProvider<Polygon>> Layer for ObstaclesLayer<P> {
+	fn draw(&mut self, size: LogicalSize<f32>) {
+		self.vertex_model.bind();
+		self.vertex_buffer.bind();
+		self.shader_program.activate();
+
+		let num_vertices = self.write_vertices(size);
+		if num_vertices > 0 {
+			self.ctx.draw(glow::TRIANGLES, num_vertices);
+		}
+	}
+}
+
+impl From<&Obstacle> for Polygon {
+	fn from(value: &Obstacle) -> Self {
+		Polygon {
+			vertices: value.outline()
+		}
+	}
+}
+
+impl ObjectProvider<Polygon> for Simulation {
+	fn iter_objects(&self) -> Box<dyn Iterator<Item = Polygon> + '_> {
+		let iter = self.obstacles().iter().map(|obstacle| obstacle.into());
+		Box::new(iter)
+	}
+}
@@ -1,5 +1,6 @@
 use std::sync::{Arc, Mutex};
 
+use cell_life::particles::ParticleSystem;
 use glam::{vec2, Vec2, Vec3};
 use winit::dpi::LogicalSize;
 
@@ -133,3 +134,25 @@ impl<P: ObjectProvider<ParticleGroup>> Layer for ParticlesLayer<P> {
 		self.draw_groups(size);
 	}
 }
+
+impl ObjectProvider<ParticleGroup> for ParticleSystem {
+	fn iter_objects(&self) -> Box<dyn Iterator<Item = ParticleGroup> + '_> {
+		Box::new(self.groups().iter().map(|group| {
+			ParticleGroup::new(
+				group.color(),
+				group
+					.particles()
+					.iter()
+					.map(|particle| {
+						Particle::new(
+							particle.opacity(),
+							particle.position(),
+							particle.rotation(),
+							particle.shape().to_vec()
+						)
+					})
+					.collect()
+			)
+		}))
+	}
+}
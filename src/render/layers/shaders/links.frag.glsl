@@ -0,0 +1,13 @@
+#version 430 core
+
+precision mediump float;
+
+const float LINK_OPACITY = 0.6;
+
+in vec3 frag_color;
+
+out vec4 pixel_color;
+
+void main() {
+	pixel_color = vec4(frag_color, LINK_OPACITY);
+}
\ No newline at end of file
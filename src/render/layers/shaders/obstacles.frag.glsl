@@ -0,0 +1,11 @@
+#version 430 core
+
+precision mediump float;
+
+const vec3 OBSTACLE_COLOR = vec3(0.25, 0.25, 0.3);
+
+out vec4 pixel_color;
+
+void main() {
+	pixel_color = vec4(OBSTACLE_COLOR, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use std::{fmt::Display, fs::File, io::BufWriter, path::Path, process};
+
+use cell_life::{
+	particles::ParticleSystem,
+	rand,
+	sim::{scenario::Scenario, snapshot::Snapshot, stats::StatsWriter, Simulation}
+};
+
+use crate::cli::Args;
+
+const DEFAULT_SCENARIO: &str = include_str!("../scenarios/default.toml");
+
+pub fn create_simulation(args: &Args) -> (Simulation, ParticleSystem) {
+	let (mut simulation, particle_system) = match &args.load {
+		Some(path) => {
+			let snapshot = Snapshot::load(path).unwrap_or_else(|err| {
+				exit_with_error(format!("Failed to load snapshot {}: {err}", path.display()))
+			});
+			let (simulation, particle_system) = snapshot.restore();
+			let particle_system =
+				particle_system.unwrap_or_else(|| ParticleSystem::new(simulation.particle_seed()));
+			(simulation, particle_system)
+		}
+		None => {
+			let scenario = match &args.scenario {
+				Some(path) => Scenario::load(path).unwrap_or_else(|err| {
+					exit_with_error(format!("Failed to load scenario {}: {err}", path.display()))
+				}),
+				None => Scenario::parse(DEFAULT_SCENARIO).expect("Invalid built-in scenario")
+			};
+			let seed = args
+				.seed
+				.or(scenario.seed)
+				.unwrap_or_else(rand::random_seed);
+			println!("Simulation seed: {seed}");
+			let mut simulation = scenario.build(seed);
+			if let Some(mode) = args.long_range_mode() {
+				simulation.set_long_range_mode(mode);
+			}
+			if let Some(bounds) = args.bounds {
+				simulation.set_bounds(bounds);
+			}
+			let particle_system = ParticleSystem::new(simulation.particle_seed());
+			(simulation, particle_system)
+		}
+	};
+	if let Err(err) = simulation.set_threads(args.threads()) {
+		eprintln!("Failed to start worker threads, updating cells on one thread: {err}");
+	}
+	simulation.set_stats_config(args.stats_config());
+	if let Some(path) = &args.stats {
+		let file = File::create(path).unwrap_or_else(|err| {
+			exit_with_error(format!(
+				"Failed to create statistics file {}: {err}",
+				path.display()
+			))
+		});
+		let writer = StatsWriter::new(args.stats_format(), Box::new(BufWriter::new(file)));
+		simulation.add_stats_writer(writer);
+	}
+	if let Some(path) = &args.events {
+		let file = File::create(path).unwrap_or_else(|err| {
+			exit_with_error(format!(
+				"Failed to create event log {}: {err}",
+				path.display()
+			))
+		});
+		simulation.add_event_log(Box::new(BufWriter::new(file)), args.event_filter());
+	}
+	(simulation, particle_system)
+}
+
+pub fn save_snapshot(path: &Path, simulation: &Simulation, particle_system: &ParticleSystem) {
+	let snapshot = Snapshot::capture(simulation, Some(particle_system));
+	match snapshot.save(path) {
+		Ok(()) => println!("Saved snapshot to {}", path.display()),
+		Err(err) => eprintln!("Failed to save snapshot {}: {err}", path.display())
+	}
+}
+
+/// Reports a problem with the user's input and exits, without the backtrace a panic would print.
+pub fn exit_with_error(message: impl Display) -> ! {
+	eprintln!("{message}");
+	process::exit(1)
+}
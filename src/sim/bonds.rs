@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{bounds::WorldBounds, cell::Cell};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BondConfig {
+	pub stiffness: f32,
+	pub damping_ratio: f32,
+	pub break_strain: f32,
+	pub energy_sharing_rate: f64,
+	pub max_bonds_per_cell: usize
+}
+
+impl Default for BondConfig {
+	fn default() -> Self {
+		Self {
+			stiffness: 2000.0,
+			damping_ratio: 1.0,
+			break_strain: 0.5,
+			energy_sharing_rate: 0.0,
+			max_bonds_per_cell: 6
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bond {
+	pub rest_length: f32,
+	pub strength: f32
+}
+
+#[derive(Debug, Clone)]
+pub struct Bonds {
+	config: BondConfig,
+	bonds: BTreeMap<(Uuid, Uuid), Bond>,
+	counts: BTreeMap<Uuid, usize>
+}
+
+fn key(a: Uuid, b: Uuid) -> (Uuid, Uuid) {
+	if a < b {
+		(a, b)
+	} else {
+		(b, a)
+	}
+}
+
+impl Bonds {
+	pub fn new(config: BondConfig) -> Self {
+		Self {
+			config,
+			bonds: BTreeMap::new(),
+			counts: BTreeMap::new()
+		}
+	}
+
+	pub fn config(&self) -> BondConfig {
+		self.config
+	}
+
+	pub fn set_config(&mut self, config: BondConfig) {
+		self.config = config;
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (Uuid, Uuid, &Bond)> + '_ {
+		self.bonds.iter().map(|(&(a, b), bond)| (a, b, bond))
+	}
+
+	pub fn form(&mut self, a: Uuid, b: Uuid, bond: Bond) -> bool {
+		let max_bonds = self.config.max_bonds_per_cell;
+		if a == b
+			|| self.bonds.contains_key(&key(a, b))
+			|| self.count(a) >= max_bonds
+			|| self.count(b) >= max_bonds
+		{
+			return false;
+		}
+		self.bonds.insert(key(a, b), bond);
+		*self.counts.entry(a).or_default() += 1;
+		*self.counts.entry(b).or_default() += 1;
+		true
+	}
+
+	/// Removes all bonds of a cell and returns its former partners along with their bonds.
+	pub fn detach(&mut self, id: Uuid) -> Vec<(Uuid, Bond)> {
+		if self.counts.remove(&id).is_none() {
+			return Vec::new();
+		}
+		let keys: Vec<(Uuid, Uuid)> = self
+			.bonds
+			.keys()
+			.filter(|(a, b)| *a == id || *b == id)
+			.copied()
+			.collect();
+		keys.into_iter()
+			.map(|(a, b)| {
+				let partner = if a == id { b } else { a };
+				let bond = self.bonds.remove(&(a, b)).unwrap();
+				self.decrement(partner);
+				(partner, bond)
+			})
+			.collect()
+	}
+
+	pub fn forces(&self, cells: &[Cell], bounds: &WorldBounds) -> Vec<Vec2> {
+		// `cells` is sorted by ID, as it comes from the simulation's snapshot.
+		let index_of = |id: Uuid| cells.binary_search_by_key(&id, |cell| cell.id).ok();
+		let mut forces = vec![Vec2::ZERO; cells.len()];
+		for (&(a, b), bond) in &self.bonds {
+			let (Some(a), Some(b)) = (index_of(a), index_of(b)) else {
+				continue;
+			};
+			let force = self.force(&cells[a], &cells[b], bond, bounds);
+			forces[a] += force;
+			forces[b] -= force;
+		}
+		forces
+	}
+
+	pub fn break_strained(&mut self, cells: &BTreeMap<Uuid, Cell>, bounds: &WorldBounds) {
+		let broken: Vec<(Uuid, Uuid)> = self
+			.bonds
+			.iter()
+			.filter(|(&(a, b), bond)| match (cells.get(&a), cells.get(&b)) {
+				(Some(a), Some(b)) => {
+					let length = bounds.displacement(a.position, b.position).length();
+					// Compared without dividing, so that a bond of zero rest length doesn't give
+					// an infinite or undefined strain.
+					length - bond.rest_length > self.config.break_strain * bond.rest_length
+				}
+				_ => true
+			})
+			.map(|(key, _)| *key)
+			.collect();
+		for (a, b) in broken {
+			self.bonds.remove(&(a, b));
+			self.decrement(a);
+			self.decrement(b);
+		}
+	}
+
+	pub fn share_energy(&self, cells: &mut BTreeMap<Uuid, Cell>, dt: f64) {
+		let fraction = (self.config.energy_sharing_rate * dt).min(1.0) / 2.0;
+		if fraction <= 0.0 {
+			return;
+		}
+		for &(a, b) in self.bonds.keys() {
+			let (Some(a_energy), Some(b_energy)) = (
+				cells.get(&a).map(|cell| cell.energy),
+				cells.get(&b).map(|cell| cell.energy)
+			) else {
+				continue;
+			};
+			let flow = fraction * (a_energy - b_energy);
+			cells.get_mut(&a).unwrap().energy -= flow;
+			cells.get_mut(&b).unwrap().energy += flow;
+		}
+	}
+
+	fn force(&self, cell: &Cell, other_cell: &Cell, bond: &Bond, bounds: &WorldBounds) -> Vec2 {
+		let displacement = bounds.displacement(cell.position, other_cell.position);
+		let Some(direction) = displacement.try_normalize() else {
+			return Vec2::ZERO;
+		};
+		let mass = cell.mass();
+		let other_mass = other_cell.mass();
+		if mass <= 0.0 || other_mass <= 0.0 {
+			return Vec2::ZERO;
+		}
+		let reduced_mass = mass * other_mass / (mass + other_mass);
+		let stiffness = self.config.stiffness * bond.strength * reduced_mass;
+		let damping = 2.0 * self.config.damping_ratio * (stiffness * reduced_mass).sqrt();
+
+		let stretch = displacement.length() - bond.rest_length;
+		let stretch_velocity = (other_cell.velocity - cell.velocity).dot(direction);
+		(stiffness * stretch + damping * stretch_velocity) * direction
+	}
+
+	fn count(&self, id: Uuid) -> usize {
+		self.counts.get(&id).copied().unwrap_or(0)
+	}
+
+	fn decrement(&mut self, id: Uuid) {
+		if let Some(count) = self.counts.get_mut(&id) {
+			*count -= 1;
+			if *count == 0 {
+				self.counts.remove(&id);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn cells(distance: f32) -> BTreeMap<Uuid, Cell> {
+		[
+			Cell::test_cell(1, 5.0, Vec2::ZERO),
+			Cell::test_cell(2, 5.0, Vec2::new(distance, 0.0))
+		]
+		.into_iter()
+		.map(|cell| (cell.id, cell))
+		.collect()
+	}
+
+	fn bond(rest_length: f32) -> Bond {
+		Bond {
+			rest_length,
+			strength: 1.0
+		}
+	}
+
+	fn bonded(rest_length: f32) -> Bonds {
+		let mut bonds = Bonds::new(BondConfig::default());
+		assert!(bonds.form(Uuid::from_u128(1), Uuid::from_u128(2), bond(rest_length)));
+		bonds
+	}
+
+	#[test]
+	fn forming_respects_duplicates_and_limits() {
+		let id = Uuid::from_u128;
+		let mut bonds = Bonds::new(BondConfig {
+			max_bonds_per_cell: 2,
+			..BondConfig::default()
+		});
+		assert!(!bonds.form(id(1), id(1), bond(10.0)));
+		assert!(bonds.form(id(1), id(2), bond(10.0)));
+		assert!(!bonds.form(id(2), id(1), bond(10.0)));
+		assert!(bonds.form(id(1), id(3), bond(10.0)));
+		assert!(!bonds.form(id(1), id(4), bond(10.0)));
+
+		let partners: Vec<Uuid> = bonds.detach(id(1)).into_iter().map(|(id, _)| id).collect();
+		assert_eq!(partners, vec![id(2), id(3)]);
+		assert!(bonds.form(id(2), id(3), bond(10.0)));
+		assert!(bonds.form(id(1), id(4), bond(10.0)));
+	}
+
+	#[test]
+	fn bonds_break_past_the_strain_threshold() {
+		// The default break strain of 0.5 lets a bond of rest length 10 stretch to 15.
+		let bounds = WorldBounds::Open;
+		for (distance, breaks) in [(10.0, false), (5.0, false), (14.9, false), (15.1, true)] {
+			let mut bonds = bonded(10.0);
+			bonds.break_strained(&cells(distance), &bounds);
+			assert_eq!(bonds.iter().count() == 0, breaks, "distance {distance}");
+		}
+
+		// A bond with no rest length breaks as soon as it is stretched at all.
+		let mut bonds = bonded(0.0);
+		bonds.break_strained(&cells(0.0), &bounds);
+		assert_eq!(bonds.iter().count(), 1);
+		bonds.break_strained(&cells(0.1), &bounds);
+		assert_eq!(bonds.iter().count(), 0);
+	}
+
+	#[test]
+	fn bonds_pull_towards_their_rest_length() {
+		let bonds = bonded(10.0);
+		let bounds = WorldBounds::Open;
+		let forces = |distance| {
+			let cells: Vec<Cell> = cells(distance).into_values().collect();
+			bonds.forces(&cells, &bounds)
+		};
+		assert_eq!(forces(10.0), vec![Vec2::ZERO, Vec2::ZERO]);
+		let stretched = forces(12.0);
+		assert!(stretched[0].x > 0.0 && stretched[1].x < 0.0);
+		assert_eq!(stretched[0], -stretched[1]);
+		let compressed = forces(8.0);
+		assert!(compressed[0].x < 0.0 && compressed[1].x > 0.0);
+	}
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use super::cell::Cell;
+
+/// Deserialized through the checked constructors, so scenarios and snapshots can't describe a
+/// world with zero, negative or infinite dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", try_from = "BoundsSpec")]
+pub enum WorldBounds {
+	#[default]
+	Open,
+	Rectangle {
+		size: Vec2
+	},
+	Circle {
+		radius: f32
+	},
+	Torus {
+		size: Vec2
+	}
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BoundsSpec {
+	Open,
+	Rectangle { size: Vec2 },
+	Circle { radius: f32 },
+	Torus { size: Vec2 }
+}
+
+impl TryFrom<BoundsSpec> for WorldBounds {
+	type Error = BoundsError;
+
+	fn try_from(value: BoundsSpec) -> Result<Self, Self::Error> {
+		match value {
+			BoundsSpec::Open => Ok(Self::Open),
+			BoundsSpec::Rectangle { size } => Self::rectangle(size),
+			BoundsSpec::Circle { radius } => Self::circle(radius),
+			BoundsSpec::Torus { size } => Self::torus(size)
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundsError {
+	dimension: f32
+}
+
+impl fmt::Display for BoundsError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"World dimensions must be positive and finite, got {}",
+			self.dimension
+		)
+	}
+}
+
+impl std::error::Error for BoundsError {}
+
+fn check_dimension(dimension: f32) -> Result<f32, BoundsError> {
+	if dimension.is_finite() && dimension > 0.0 {
+		Ok(dimension)
+	} else {
+		Err(BoundsError { dimension })
+	}
+}
+
+fn check_size(size: Vec2) -> Result<Vec2, BoundsError> {
+	Ok(Vec2::new(
+		check_dimension(size.x)?,
+		check_dimension(size.y)?
+	))
+}
+
+impl WorldBounds {
+	pub fn rectangle(size: Vec2) -> Result<Self, BoundsError> {
+		Ok(Self::Rectangle {
+			size: check_size(size)?
+		})
+	}
+
+	pub fn circle(radius: f32) -> Result<Self, BoundsError> {
+		Ok(Self::Circle {
+			radius: check_dimension(radius)?
+		})
+	}
+
+	pub fn torus(size: Vec2) -> Result<Self, BoundsError> {
+		Ok(Self::Torus {
+			size: check_size(size)?
+		})
+	}
+
+	pub fn constrain(&self, cell: &mut Cell) {
+		match *self {
+			Self::Open => (),
+			Self::Rectangle { size } => {
+				let extent = (size / 2.0 - Vec2::splat(cell.size)).max(Vec2::ZERO);
+				reflect(&mut cell.position.x, &mut cell.velocity.x, extent.x);
+				reflect(&mut cell.position.y, &mut cell.velocity.y, extent.y);
+			}
+			Self::Circle { radius } => {
+				let limit = (radius - cell.size).max(0.0);
+				let distance = cell.position.length();
+				if distance <= limit {
+					return;
+				}
+				let normal = cell.position / distance;
+				cell.position = normal * (2.0 * limit - distance).max(0.0);
+				let normal_velocity = cell.velocity.dot(normal);
+				if normal_velocity > 0.0 {
+					cell.velocity -= 2.0 * normal_velocity * normal;
+				}
+			}
+			Self::Torus { size } => {
+				let half_size = size / 2.0;
+				cell.position.x = (cell.position.x + half_size.x).rem_euclid(size.x) - half_size.x;
+				cell.position.y = (cell.position.y + half_size.y).rem_euclid(size.y) - half_size.y;
+			}
+		}
+	}
+
+	pub fn displacement(&self, from: Vec2, to: Vec2) -> Vec2 {
+		let difference = to - from;
+		match *self {
+			Self::Torus { size } => difference - size * (difference / size).round(),
+			_ => difference
+		}
+	}
+
+	/// Offsets of the copies of the world that have to be searched to find everything within
+	/// `range` of `position`. Returns `None` if the range covers more than half of a periodic
+	/// world, in which case a cell could be found more than once.
+	pub fn image_offsets(&self, position: Vec2, range: Option<f32>) -> Option<Vec<Vec2>> {
+		let Self::Torus { size } = *self else {
+			return Some(vec![Vec2::ZERO]);
+		};
+		let range = range?;
+		if 2.0 * range >= size.min_element() {
+			return None;
+		}
+
+		let half_size = size / 2.0;
+		let axis_offsets = |position: f32, half_size: f32, size: f32| {
+			let mut offsets = vec![0.0];
+			if position - range < -half_size {
+				offsets.push(size);
+			}
+			if position + range > half_size {
+				offsets.push(-size);
+			}
+			offsets
+		};
+		let x_offsets = axis_offsets(position.x, half_size.x, size.x);
+		let y_offsets = axis_offsets(position.y, half_size.y, size.y);
+		let offsets = y_offsets
+			.iter()
+			.flat_map(|&y| x_offsets.iter().map(move |&x| Vec2::new(x, y)))
+			.collect();
+		Some(offsets)
+	}
+}
+
+fn reflect(position: &mut f32, velocity: &mut f32, extent: f32) {
+	if *position < -extent {
+		*position = f32::min(-2.0 * extent - *position, extent);
+		*velocity = velocity.abs();
+	} else if *position > extent {
+		*position = f32::max(2.0 * extent - *position, -extent);
+		*velocity = -velocity.abs();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn degenerate_dimensions_are_rejected() {
+		for dimension in [0.0, -10.0, f32::NAN, f32::INFINITY] {
+			assert!(WorldBounds::circle(dimension).is_err());
+			for size in [Vec2::new(dimension, 100.0), Vec2::new(100.0, dimension)] {
+				assert!(WorldBounds::rectangle(size).is_err());
+				assert!(WorldBounds::torus(size).is_err());
+			}
+		}
+		assert!(WorldBounds::torus(Vec2::new(100.0, 50.0)).is_ok());
+	}
+
+	#[test]
+	fn deserializing_checks_dimensions() {
+		let parse = |json: &str| serde_json::from_str::<WorldBounds>(json);
+		assert_eq!(
+			parse(r#"{"circle": {"radius": 10.0}}"#).unwrap(),
+			WorldBounds::Circle { radius: 10.0 }
+		);
+		assert_eq!(parse(r#""open""#).unwrap(), WorldBounds::Open);
+		assert!(parse(r#"{"torus": {"size": [0.0, 0.0]}}"#).is_err());
+		assert!(parse(r#"{"rectangle": {"size": [-100.0, 100.0]}}"#).is_err());
+	}
+}
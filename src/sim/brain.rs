@@ -0,0 +1,168 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rand::Rng;
+
+use super::chemicals::ChemicalConfig;
+
+/// Inputs that don't depend on the number of chemical channels: three colour directions, crowding,
+/// energy, health and velocity.
+const BASE_INPUTS: usize = 11;
+/// Outputs that don't depend on the number of chemical channels: the movement direction.
+const BASE_OUTPUTS: usize = 2;
+const NUM_HIDDEN: usize = 8;
+
+pub const MAX_INITIAL_WEIGHT: f32 = 1.0;
+
+/// Each chemical channel adds a gradient input and an emission output.
+pub fn num_inputs(channels: usize) -> usize {
+	BASE_INPUTS + 2 * channels
+}
+
+pub fn num_outputs(channels: usize) -> usize {
+	BASE_OUTPUTS + channels
+}
+
+fn num_weights(channels: usize) -> usize {
+	NUM_HIDDEN * (num_inputs(channels) + 1) + num_outputs(channels) * (NUM_HIDDEN + 1)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "NetworkGene")]
+pub struct Network {
+	channels: usize,
+	weights: Vec<f32>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NetworkGene {
+	#[serde(default = "default_channels")]
+	channels: usize,
+	weights: Vec<f32>
+}
+
+fn default_channels() -> usize {
+	ChemicalConfig::default().channels
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkError {
+	channels: usize,
+	expected: usize,
+	found: usize
+}
+
+impl fmt::Display for NetworkError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"A brain sensing {} chemical channels needs {} weights, got {}",
+			self.channels, self.expected, self.found
+		)
+	}
+}
+
+impl std::error::Error for NetworkError {}
+
+impl TryFrom<NetworkGene> for Network {
+	type Error = NetworkError;
+
+	fn try_from(value: NetworkGene) -> Result<Self, Self::Error> {
+		Self::new(value.channels, value.weights)
+	}
+}
+
+impl Network {
+	pub fn new(channels: usize, weights: Vec<f32>) -> Result<Self, NetworkError> {
+		let expected = num_weights(channels);
+		if weights.len() != expected {
+			return Err(NetworkError {
+				channels,
+				expected,
+				found: weights.len()
+			});
+		}
+		Ok(Self { channels, weights })
+	}
+
+	pub fn random(rng: &mut Rng, channels: usize) -> Self {
+		let weights = (0..num_weights(channels))
+			.map(|_| rng.rand_in_range(-MAX_INITIAL_WEIGHT, MAX_INITIAL_WEIGHT))
+			.collect();
+		Self { channels, weights }
+	}
+
+	pub fn channels(&self) -> usize {
+		self.channels
+	}
+
+	pub fn weights(&self) -> &[f32] {
+		&self.weights
+	}
+
+	/// The weights can be changed but not added or removed, so the network keeps its shape.
+	pub fn weights_mut(&mut self) -> &mut [f32] {
+		&mut self.weights
+	}
+
+	pub fn evaluate(&self, inputs: &[f32]) -> Vec<f32> {
+		let num_inputs = num_inputs(self.channels);
+		assert_eq!(inputs.len(), num_inputs, "Wrong number of brain inputs");
+		let (hidden_weights, output_weights) = self.weights.split_at(NUM_HIDDEN * (num_inputs + 1));
+		let hidden: Vec<f32> = hidden_weights
+			.chunks_exact(num_inputs + 1)
+			.map(|weights| neuron(weights, inputs))
+			.collect();
+
+		output_weights
+			.chunks_exact(NUM_HIDDEN + 1)
+			.map(|weights| neuron(weights, &hidden))
+			.collect()
+	}
+}
+
+fn neuron(weights: &[f32], inputs: &[f32]) -> f32 {
+	let (bias, weights) = weights.split_last().unwrap();
+	let sum: f32 = weights
+		.iter()
+		.zip(inputs)
+		.map(|(weight, input)| weight * input)
+		.sum();
+	(sum + bias).tanh()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn weight_count_must_match_the_channels() {
+		let mut rng = Rng::new(1);
+		let network = Network::random(&mut rng, 3);
+		assert!(Network::new(3, network.weights().to_vec()).is_ok());
+		assert!(Network::new(2, network.weights().to_vec()).is_err());
+		assert!(Network::new(3, network.weights()[1..].to_vec()).is_err());
+	}
+
+	#[test]
+	fn outputs_follow_the_channels() {
+		let mut rng = Rng::new(1);
+		for channels in 0..4 {
+			let network = Network::random(&mut rng, channels);
+			let outputs = network.evaluate(&vec![0.5; num_inputs(channels)]);
+			assert_eq!(outputs.len(), num_outputs(channels));
+		}
+	}
+
+	#[test]
+	fn invalid_genes_are_rejected_when_deserialized() {
+		let json = serde_json::to_string(&Network::random(&mut Rng::new(1), 2)).unwrap();
+		let network: Network = serde_json::from_str(&json).unwrap();
+		assert_eq!(network.channels(), 2);
+
+		let error = serde_json::from_str::<Network>(r#"{"channels": 1, "weights": [1.0]}"#);
+		assert!(error.is_err());
+	}
+}
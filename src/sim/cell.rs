@@ -1,17 +1,26 @@
-use std::{f32::consts::PI, sync::Mutex};
+use std::f32::consts::PI;
 
 use glam::{Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::{
-	ecs::{Ecs, Entity},
-	render::layers
-};
+use crate::ecs::{Ecs, Entity};
 
-use super::receptors::{InteractionAccumulator, Receptor};
+use super::{
+	environment::Environment,
+	receptors::{Effect, InteractionAccumulator, Receptor},
+	spatial::{Interaction, Neighbourhood}
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cell {
+	pub id: Uuid,
 	pub entity: Entity,
+	pub parent: Option<Uuid>,
+	pub generation: u32,
+	pub birth_tick: u64,
+	/// Set when the cell dies, either killed or replaced by its daughters.
+	pub death_tick: Option<u64>,
 	pub health: f32,
 	pub size: f32,
 	pub color: Vec3,
@@ -24,6 +33,8 @@ pub struct Cell {
 const DENSITY: f32 = 1.0;
 const REGEN_SPEED: f32 = 0.2;
 const MAX_HEALTH: f32 = 3.0;
+const DIVISION_ENERGY: f64 = 20.0;
+const MIN_DIVISION_SIZE: f32 = 4.0;
 
 impl Cell {
 	pub fn consume_energy(&mut self, cost: f64) -> f32 {
@@ -43,6 +54,26 @@ impl Cell {
 		PI * self.size.powi(2) * DENSITY
 	}
 
+	pub fn set_mass(&mut self, mass: f32) {
+		self.size = (mass.max(0.0) / (PI * DENSITY)).sqrt();
+	}
+
+	pub fn can_divide(&self) -> bool {
+		self.energy >= DIVISION_ENERGY && self.size >= MIN_DIVISION_SIZE
+	}
+
+	pub fn divide(&mut self, direction: Vec2) -> Cell {
+		self.size /= 2.0_f32.sqrt();
+		self.energy /= 2.0;
+
+		let mut daughter = self.clone();
+
+		let offset = self.size * direction;
+		self.position -= offset;
+		daughter.position += offset;
+		daughter
+	}
+
 	fn handle_health(&mut self, dt: f32) {
 		if self.energy == 0.0 {
 			self.health -= dt;
@@ -62,47 +93,67 @@ impl Cell {
 
 	fn apply_effects(
 		&mut self,
-		ecs: &Mutex<Ecs<Box<dyn Receptor>>>,
-		other_cells: &[&Mutex<Cell>],
+		ecs: &Ecs<Box<dyn Receptor>>,
+		neighbourhood: &Neighbourhood,
+		environment: &Environment,
 		dt: f64
-	) {
+	) -> Vec<Effect> {
 		self.acceleration = Vec2::ZERO;
-		self.apply_receptor_effects(ecs, other_cells, dt);
+		self.apply_receptor_effects(ecs, neighbourhood, environment, dt)
 	}
 
 	fn apply_receptor_effects(
 		&mut self,
-		ecs: &Mutex<Ecs<Box<dyn Receptor>>>,
-		other_cells: &[&Mutex<Cell>],
+		ecs: &Ecs<Box<dyn Receptor>>,
+		neighbourhood: &Neighbourhood,
+		environment: &Environment,
 		dt: f64
-	) {
-		let ecs_lock = ecs.lock().unwrap();
-		let mut accumulators: Vec<Box<dyn InteractionAccumulator>> = ecs_lock
-			.components(self.entity)
+	) -> Vec<Effect> {
+		let receptors = ecs.components(self.entity);
+		let mut accumulators: Vec<Box<dyn InteractionAccumulator>> = receptors
 			.iter()
 			.map(|rec| rec.interaction_accumulator())
 			.collect();
 
-		for other_cell in other_cells {
-			for acc in &mut accumulators {
-				acc.add_interaction(self, other_cell, dt)
-			}
+		for (receptor, acc) in receptors.iter().zip(&mut accumulators) {
+			neighbourhood.for_each_interaction(self, receptor.as_ref(), |interaction| {
+				match interaction {
+					Interaction::Cell(other_cell) => acc.add_interaction(self, other_cell, dt),
+					Interaction::Cluster(cluster) => acc.add_cluster(self, &cluster, dt)
+				}
+			});
 		}
 
-		let mut force = Vec2::ZERO;
+		let mut rng = environment.rng(self.id);
+		for acc in &mut accumulators {
+			acc.sense(self, environment, &mut rng);
+		}
+
+		let mut force = neighbourhood.bond_force();
 
 		for acc in &mut accumulators {
 			force += acc.complete(self, dt);
 		}
 
 		self.apply_force(force);
+
+		let mut effects = Vec::new();
+		for acc in &mut accumulators {
+			acc.effects(self, &mut effects);
+		}
+		effects
 	}
 }
 
 impl Cell {
-	pub fn new(entity: Entity) -> Self {
+	pub fn new(id: Uuid, entity: Entity) -> Self {
 		Self {
+			id,
 			entity,
+			parent: None,
+			generation: 0,
+			birth_tick: 0,
+			death_tick: None,
 			health: 3.0,
 			size: 0.0,
 			color: Vec3::ZERO,
@@ -115,24 +166,24 @@ impl Cell {
 
 	pub fn tick(
 		&mut self,
-		ecs: &Mutex<Ecs<Box<dyn Receptor>>>,
+		ecs: &Ecs<Box<dyn Receptor>>,
 		dt: f64,
-		other_cells: &[&Mutex<Cell>]
-	) {
+		neighbourhood: &Neighbourhood,
+		environment: &Environment
+	) -> Vec<Effect> {
 		self.sim_movement(dt as f32);
 		self.handle_health(dt as f32);
-		self.apply_effects(ecs, other_cells, dt);
+		self.apply_effects(ecs, neighbourhood, environment, dt)
 	}
 }
 
-impl From<&Mutex<Cell>> for layers::dots::Dot {
-	fn from(value: &Mutex<Cell>) -> Self {
-		let state_lock = value.lock().unwrap();
-		layers::dots::Dot {
-			coords: state_lock.position,
-			radius: state_lock.size,
-			color: state_lock.color,
-			brightness: state_lock.energy as f32
-		}
+#[cfg(test)]
+impl Cell {
+	/// A cell without receptors, for tests that only need its position and size.
+	pub fn test_cell(id: u128, size: f32, position: Vec2) -> Self {
+		let mut cell = Self::new(Uuid::from_u128(id), Ecs::<()>::new().entity());
+		cell.size = size;
+		cell.position = position;
+		cell
 	}
 }
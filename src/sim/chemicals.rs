@@ -0,0 +1,94 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use super::{
+	bounds::WorldBounds,
+	field::{FieldConfig, ScalarField}
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChemicalConfig {
+	/// Layout of each channel's field.
+	pub field: FieldConfig,
+	pub channels: usize,
+	pub decay_rate: f32
+}
+
+impl Default for ChemicalConfig {
+	fn default() -> Self {
+		Self {
+			field: FieldConfig {
+				diffusion_rate: 200.0,
+				..FieldConfig::default()
+			},
+			channels: 2,
+			decay_rate: 0.1
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChemicalFields {
+	config: ChemicalConfig,
+	fields: Vec<ScalarField>
+}
+
+const HALF_SATURATION: f32 = 0.1;
+
+impl ChemicalFields {
+	pub fn new(config: ChemicalConfig, bounds: &WorldBounds) -> Self {
+		let fields = (0..config.channels)
+			.map(|_| config.field.build(bounds, 0.0))
+			.collect();
+		Self { config, fields }
+	}
+
+	pub fn config(&self) -> ChemicalConfig {
+		self.config
+	}
+
+	pub fn tick(&mut self, dt: f64) {
+		let dt = dt as f32;
+		let decay = (-self.config.decay_rate * dt).exp();
+		for field in &mut self.fields {
+			for value in field.values_mut() {
+				*value *= decay;
+			}
+			field.diffuse(self.config.field.diffusion_rate, dt);
+		}
+	}
+
+	pub fn emit(&mut self, channel: usize, position: Vec2, amount: f32) {
+		let Some(field) = self.fields.get_mut(channel) else {
+			return;
+		};
+		if let Some(index) = field.tile_index(position) {
+			*field.value_mut(index) += amount;
+		}
+	}
+
+	pub fn concentration(&self, channel: usize, position: Vec2) -> f32 {
+		self.fields
+			.get(channel)
+			.map_or(0.0, |field| field.sample(position))
+	}
+
+	pub fn gradient(&self, channel: usize, position: Vec2) -> Vec2 {
+		let Some(field) = self.fields.get(channel) else {
+			return Vec2::ZERO;
+		};
+		let step = field.tile_size();
+		let dx =
+			field.sample(position + Vec2::X * step.x) - field.sample(position - Vec2::X * step.x);
+		let dy =
+			field.sample(position + Vec2::Y * step.y) - field.sample(position - Vec2::Y * step.y);
+		Vec2::new(dx, dy) / (2.0 * step)
+	}
+
+	/// Gradient relative to the local concentration, so that cells respond to faint trails about
+	/// as strongly as to steep ones close to the source.
+	pub fn relative_gradient(&self, channel: usize, position: Vec2) -> Vec2 {
+		self.gradient(channel, position) / (self.concentration(channel, position) + HALF_SATURATION)
+	}
+}
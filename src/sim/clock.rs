@@ -0,0 +1,257 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimestepConfig {
+	pub step: f64,
+	pub max_steps: u32
+}
+
+const DEFAULT_STEP: f64 = 1.0 / 120.0;
+const DEFAULT_MAX_STEPS: u32 = 8;
+
+impl Default for TimestepConfig {
+	fn default() -> Self {
+		Self {
+			step: DEFAULT_STEP,
+			max_steps: DEFAULT_MAX_STEPS
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct FixedTimestep {
+	config: TimestepConfig,
+	accumulator: f64
+}
+
+impl FixedTimestep {
+	pub fn new(config: TimestepConfig) -> Self {
+		assert!(config.step > 0.0, "Timestep must be positive");
+		Self {
+			config,
+			accumulator: 0.0
+		}
+	}
+
+	pub fn step(&self) -> f64 {
+		self.config.step
+	}
+
+	pub fn advance(&mut self, dt: f64) -> u32 {
+		self.advance_scaled(dt, 1.0)
+	}
+
+	/// Advances by `dt` of real time running `speed` times faster. The catch-up limit grows with
+	/// the speed, so that fast-forwarding isn't capped by it.
+	pub fn advance_scaled(&mut self, dt: f64, speed: f64) -> u32 {
+		let max_steps = (self.config.max_steps as f64 * speed.max(1.0)).ceil();
+		self.accumulator += dt * speed;
+		let steps = (self.accumulator / self.config.step).floor();
+		if steps > max_steps {
+			// The simulation can't keep up; drop the backlog instead of trying to catch up on it
+			// in later frames, which would only make the next frame slower still.
+			self.accumulator %= self.config.step;
+			return max_steps as u32;
+		}
+		self.accumulator -= steps * self.config.step;
+		steps as u32
+	}
+
+	pub fn reset(&mut self) {
+		self.accumulator = 0.0;
+	}
+}
+
+pub const MIN_SPEED: f64 = 0.1;
+pub const MAX_SPEED: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+	Scaled(f64),
+	/// Runs as many steps as possible, regardless of how much real time passes.
+	Unlimited
+}
+
+impl fmt::Display for Speed {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Scaled(speed) => write!(f, "{speed}x"),
+			Self::Unlimited => write!(f, "as fast as possible")
+		}
+	}
+}
+
+/// Turns real time into simulation steps, with support for pausing, single-stepping and running
+/// faster or slower than real time.
+#[derive(Debug)]
+pub struct SimClock {
+	timestep: FixedTimestep,
+	speed: Speed,
+	paused: bool,
+	queued_steps: u32
+}
+
+impl SimClock {
+	pub fn new(config: TimestepConfig) -> Self {
+		Self {
+			timestep: FixedTimestep::new(config),
+			speed: Speed::Scaled(1.0),
+			paused: false,
+			queued_steps: 0
+		}
+	}
+
+	pub fn step_size(&self) -> f64 {
+		self.timestep.step()
+	}
+
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+
+	pub fn pause(&mut self) {
+		self.paused = true;
+	}
+
+	pub fn resume(&mut self) {
+		self.paused = false;
+		self.timestep.reset();
+	}
+
+	pub fn toggle_pause(&mut self) {
+		if self.paused {
+			self.resume();
+		} else {
+			self.pause();
+		}
+	}
+
+	/// Runs the given number of steps on the next advance, even while paused.
+	pub fn step(&mut self, steps: u32) {
+		self.queued_steps = self.queued_steps.saturating_add(steps);
+	}
+
+	pub fn speed(&self) -> Speed {
+		self.speed
+	}
+
+	pub fn set_speed(&mut self, speed: Speed) {
+		self.speed = match speed {
+			Speed::Scaled(speed) => Speed::Scaled(speed.clamp(MIN_SPEED, MAX_SPEED)),
+			Speed::Unlimited => Speed::Unlimited
+		};
+		self.timestep.reset();
+	}
+
+	pub fn faster(&mut self) {
+		if let Speed::Scaled(speed) = self.speed {
+			self.set_speed(Speed::Scaled(speed * 2.0));
+		}
+	}
+
+	pub fn slower(&mut self) {
+		let speed = match self.speed {
+			Speed::Scaled(speed) => speed / 2.0,
+			Speed::Unlimited => MAX_SPEED
+		};
+		self.set_speed(Speed::Scaled(speed));
+	}
+
+	/// Whether steps should be run continuously instead of only the ones returned by `advance`.
+	pub fn is_unlimited(&self) -> bool {
+		!self.paused && self.speed == Speed::Unlimited
+	}
+
+	/// Returns the number of steps to run for `dt` of real time.
+	pub fn advance(&mut self, dt: f64) -> u32 {
+		let queued = std::mem::take(&mut self.queued_steps);
+		if self.paused {
+			return queued;
+		}
+		match self.speed {
+			Speed::Scaled(speed) => queued.saturating_add(self.timestep.advance_scaled(dt, speed)),
+			Speed::Unlimited => queued
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const CONFIG: TimestepConfig = TimestepConfig {
+		step: 0.125,
+		max_steps: 4
+	};
+
+	#[test]
+	fn fixed_timestep_carries_over_partial_steps() {
+		let mut timestep = FixedTimestep::new(CONFIG);
+		assert_eq!(timestep.advance(0.1), 0);
+		assert_eq!(timestep.advance(0.1), 1);
+		assert_eq!(timestep.advance(0.3), 3);
+		assert_eq!(timestep.advance(0.3), 2);
+	}
+
+	#[test]
+	fn fixed_timestep_drops_the_backlog_past_the_catch_up_limit() {
+		let mut timestep = FixedTimestep::new(CONFIG);
+		assert_eq!(timestep.advance(10.0), 4);
+		assert_eq!(timestep.advance(0.0), 0);
+		// Running faster raises the limit along with the speed.
+		assert_eq!(timestep.advance_scaled(10.0, 2.0), 8);
+	}
+
+	#[test]
+	fn paused_clock_only_runs_queued_steps() {
+		let mut clock = SimClock::new(CONFIG);
+		clock.pause();
+		assert_eq!(clock.advance(1.0), 0);
+		clock.step(3);
+		assert_eq!(clock.advance(1.0), 3);
+		assert_eq!(clock.advance(1.0), 0);
+
+		clock.toggle_pause();
+		assert!(!clock.is_paused());
+		assert_eq!(clock.advance(0.25), 2);
+		clock.step(1);
+		assert_eq!(clock.advance(0.25), 3);
+	}
+
+	#[test]
+	fn queued_steps_saturate() {
+		let mut clock = SimClock::new(CONFIG);
+		clock.step(u32::MAX);
+		clock.step(1);
+		assert_eq!(clock.advance(0.25), u32::MAX);
+	}
+
+	#[test]
+	fn speed_is_clamped() {
+		let mut clock = SimClock::new(CONFIG);
+		clock.set_speed(Speed::Scaled(1000.0));
+		assert_eq!(clock.speed(), Speed::Scaled(MAX_SPEED));
+		clock.faster();
+		assert_eq!(clock.speed(), Speed::Scaled(MAX_SPEED));
+		clock.set_speed(Speed::Scaled(0.0));
+		assert_eq!(clock.speed(), Speed::Scaled(MIN_SPEED));
+		clock.slower();
+		assert_eq!(clock.speed(), Speed::Scaled(MIN_SPEED));
+
+		clock.set_speed(Speed::Scaled(2.0));
+		assert_eq!(clock.advance(0.25), 4);
+	}
+
+	#[test]
+	fn unlimited_speed_runs_continuously() {
+		let mut clock = SimClock::new(CONFIG);
+		clock.set_speed(Speed::Unlimited);
+		assert!(clock.is_unlimited());
+		assert_eq!(clock.advance(1.0), 0);
+		clock.pause();
+		assert!(!clock.is_unlimited());
+		clock.resume();
+		clock.slower();
+		assert_eq!(clock.speed(), Speed::Scaled(MAX_SPEED));
+	}
+}
@@ -0,0 +1,23 @@
+use uuid::Uuid;
+
+use crate::rand::Rng;
+
+use super::chemicals::ChemicalFields;
+
+pub struct Environment<'a> {
+	pub chemicals: &'a ChemicalFields,
+	seed: u64
+}
+
+impl<'a> Environment<'a> {
+	pub fn new(chemicals: &'a ChemicalFields, seed: u64) -> Self {
+		Self { chemicals, seed }
+	}
+
+	/// Each cell draws from its own stream, so random decisions don't depend on the order cells
+	/// are updated in.
+	pub fn rng(&self, id: Uuid) -> Rng {
+		let (high, low) = id.as_u64_pair();
+		Rng::new(self.seed ^ high ^ low.rotate_left(32))
+	}
+}
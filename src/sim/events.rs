@@ -0,0 +1,138 @@
+use std::{
+	io::{self, Write},
+	sync::mpsc::{self, Receiver, Sender}
+};
+
+use glam::{Vec2, Vec3};
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+	Birth {
+		id: Uuid,
+		parent: Option<Uuid>
+	},
+	Death {
+		id: Uuid,
+		position: Vec2,
+		color: Vec3,
+		size: f32
+	},
+	Division {
+		parent: Uuid,
+		daughters: [Uuid; 2]
+	},
+	Predation {
+		predator: Uuid,
+		prey: Uuid,
+		strength: f32
+	},
+	EnergySpent {
+		id: Uuid,
+		amount: f64
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Event {
+	pub tick: u64,
+	#[serde(flatten)]
+	pub kind: EventKind
+}
+
+type Filter = Box<dyn Fn(&EventKind) -> bool + Send>;
+
+struct Subscriber {
+	sender: Sender<Event>,
+	filter: Filter
+}
+
+struct EventLog {
+	output: Box<dyn Write + Send>,
+	filter: Filter
+}
+
+impl EventLog {
+	fn write(&mut self, event: &Event) -> io::Result<()> {
+		serde_json::to_writer(&mut self.output, event)?;
+		writeln!(self.output)
+	}
+}
+
+#[derive(Default)]
+pub struct EventBus {
+	subscribers: Vec<Subscriber>,
+	logs: Vec<EventLog>
+}
+
+impl EventBus {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn subscribe<F: Fn(&EventKind) -> bool + Send + 'static>(
+		&mut self,
+		filter: F
+	) -> Receiver<Event> {
+		let (sender, receiver) = mpsc::channel();
+		self.subscribers.push(Subscriber {
+			sender,
+			filter: Box::new(filter)
+		});
+		receiver
+	}
+
+	pub fn add_log<F: Fn(&EventKind) -> bool + Send + 'static>(
+		&mut self,
+		output: Box<dyn Write + Send>,
+		filter: F
+	) {
+		self.logs.push(EventLog {
+			output,
+			filter: Box::new(filter)
+		});
+	}
+
+	pub fn flush(&mut self) {
+		self.logs.retain_mut(|log| match log.output.flush() {
+			Ok(()) => true,
+			Err(err) => {
+				eprintln!("Failed to write event log, closing it: {err}");
+				false
+			}
+		});
+	}
+
+	pub fn emit(&mut self, tick: u64, kind: EventKind) {
+		if self.subscribers.is_empty() && self.logs.is_empty() {
+			return;
+		}
+		let event = Event { tick, kind };
+		self.subscribers.retain(|subscriber| {
+			!(subscriber.filter)(&event.kind) || subscriber.sender.send(event.clone()).is_ok()
+		});
+		self.logs.retain_mut(|log| {
+			if !(log.filter)(&event.kind) {
+				return true;
+			}
+			match log.write(&event) {
+				Ok(()) => true,
+				Err(err) => {
+					eprintln!("Failed to write event log, closing it: {err}");
+					false
+				}
+			}
+		});
+	}
+}
+
+impl std::fmt::Debug for EventBus {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("EventBus")
+			.field("subscribers", &self.subscribers.len())
+			.field("logs", &self.logs.len())
+			.finish()
+	}
+}
@@ -0,0 +1,308 @@
+use glam::{IVec2, UVec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::bounds::WorldBounds;
+
+/// Layout and diffusion of a field spread over the world, shared by nutrients and chemicals.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldConfig {
+	/// Size of the field in an open world. Bounded worlds are covered exactly.
+	pub size: Vec2,
+	pub tile_size: f32,
+	pub diffusion_rate: f32
+}
+
+impl Default for FieldConfig {
+	fn default() -> Self {
+		Self {
+			size: Vec2::splat(4000.0),
+			tile_size: 20.0,
+			diffusion_rate: 50.0
+		}
+	}
+}
+
+impl FieldConfig {
+	/// A field covering the given world, filled with `initial_value`.
+	pub fn build(&self, bounds: &WorldBounds, initial_value: f32) -> ScalarField {
+		let tile_size = self.tile_size;
+		match *bounds {
+			WorldBounds::Open => ScalarField::centered(self.size, tile_size, initial_value),
+			WorldBounds::Rectangle { size } => {
+				ScalarField::centered(size, tile_size, initial_value)
+			}
+			WorldBounds::Circle { radius } => {
+				ScalarField::centered(Vec2::splat(2.0 * radius), tile_size, initial_value)
+			}
+			WorldBounds::Torus { size } => ScalarField::periodic(size, tile_size, initial_value)
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalarField {
+	origin: Vec2,
+	tile_size: Vec2,
+	dimensions: UVec2,
+	/// Whether the field wraps around at its edges, as it does in a toroidal world.
+	periodic: bool,
+	values: Vec<f32>,
+	#[serde(skip)]
+	scratch: Vec<f32>
+}
+
+const MAX_DIFFUSION_FACTOR: f32 = 0.25;
+
+impl ScalarField {
+	pub fn new(
+		origin: Vec2,
+		tile_size: Vec2,
+		dimensions: UVec2,
+		periodic: bool,
+		initial_value: f32
+	) -> Self {
+		assert!(
+			tile_size.min_element() > 0.0,
+			"Field tile size must be positive"
+		);
+		let num_tiles = (dimensions.x * dimensions.y) as usize;
+		Self {
+			origin,
+			tile_size,
+			dimensions,
+			periodic,
+			values: vec![initial_value; num_tiles],
+			scratch: Vec::with_capacity(num_tiles)
+		}
+	}
+
+	pub fn centered(size: Vec2, tile_size: f32, initial_value: f32) -> Self {
+		let dimensions = (size / tile_size).ceil().as_uvec2().max(UVec2::ONE);
+		let origin = -dimensions.as_vec2() * tile_size / 2.0;
+		Self::new(
+			origin,
+			Vec2::splat(tile_size),
+			dimensions,
+			false,
+			initial_value
+		)
+	}
+
+	/// A field spanning exactly one period of a toroidal world. The tiles are stretched slightly
+	/// where the size isn't a multiple of the tile size, so that the edges line up.
+	pub fn periodic(size: Vec2, tile_size: f32, initial_value: f32) -> Self {
+		let dimensions = (size / tile_size).round().as_uvec2().max(UVec2::ONE);
+		let tile_size = size / dimensions.as_vec2();
+		Self::new(-size / 2.0, tile_size, dimensions, true, initial_value)
+	}
+
+	pub fn tile_size(&self) -> Vec2 {
+		self.tile_size
+	}
+
+	/// Maps tile coordinates that may lie outside the field to the tile they refer to, if any.
+	fn tile_coords(&self, coords: IVec2) -> Option<UVec2> {
+		let dimensions = self.dimensions.as_ivec2();
+		if self.periodic {
+			let coords = IVec2::new(
+				coords.x.rem_euclid(dimensions.x),
+				coords.y.rem_euclid(dimensions.y)
+			);
+			return Some(coords.as_uvec2());
+		}
+		if coords.cmplt(IVec2::ZERO).any() || coords.cmpge(dimensions).any() {
+			return None;
+		}
+		Some(coords.as_uvec2())
+	}
+
+	fn index(&self, coords: UVec2) -> usize {
+		(coords.y * self.dimensions.x + coords.x) as usize
+	}
+
+	pub fn tile_index(&self, position: Vec2) -> Option<usize> {
+		let coords = ((position - self.origin) / self.tile_size).floor();
+		if !coords.is_finite() {
+			return None;
+		}
+		self.tile_coords(coords.as_ivec2())
+			.map(|coords| self.index(coords))
+	}
+
+	pub fn tiles_in_circle(&self, center: Vec2, radius: f32) -> impl Iterator<Item = usize> + '_ {
+		let size = self.dimensions.as_vec2() * self.tile_size;
+		let mut offset = center - self.origin;
+		if self.periodic {
+			// Moving the circle into the field first keeps the tile range small. Distances are
+			// measured to the closest copy of each tile, so where the range starts doesn't matter.
+			offset -= size * (offset / size).floor();
+		}
+		let min = ((offset - radius) / self.tile_size).floor();
+		let max = ((offset + radius) / self.tile_size).floor();
+		let last = (self.dimensions - UVec2::ONE).as_vec2();
+		let (min, max) = if self.periodic {
+			// A circle wider than the field would otherwise visit some tiles twice.
+			(min, max.min(min + last))
+		} else {
+			(min.max(Vec2::ZERO), max.min(last))
+		};
+		let (min, max) = (min.as_ivec2(), max.as_ivec2());
+		let center_index = self.tile_index(center);
+		let radius_squared = radius * radius;
+
+		(min.y..=max.y)
+			.flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+			.filter_map(move |coords| {
+				let tile_center = (coords.as_vec2() + 0.5) * self.tile_size;
+				let index = self.index(self.tile_coords(coords)?);
+				Some((index, tile_center))
+			})
+			.filter(move |(index, tile_center)| {
+				let mut difference = *tile_center - offset;
+				if self.periodic {
+					difference -= size * (difference / size).round();
+				}
+				Some(*index) == center_index || difference.length_squared() <= radius_squared
+			})
+			.map(|(index, _)| index)
+	}
+
+	pub fn sample(&self, position: Vec2) -> f32 {
+		// Interpolates bilinearly between tile centers. Outside a bounded field the edge values
+		// are held, while a periodic field interpolates across its edges.
+		let last = self.dimensions - UVec2::ONE;
+		let mut coords = (position - self.origin) / self.tile_size - 0.5;
+		if self.periodic {
+			let dimensions = self.dimensions.as_vec2();
+			coords -= dimensions * (coords / dimensions).floor();
+		} else {
+			coords = coords.clamp(Vec2::ZERO, last.as_vec2());
+		}
+		let base = coords.floor();
+		let t = coords - base;
+		let base = base.as_uvec2().min(last);
+		let next = if self.periodic {
+			(base + UVec2::ONE) % self.dimensions
+		} else {
+			(base + UVec2::ONE).min(last)
+		};
+		let value = |x: u32, y: u32| self.values[self.index(UVec2::new(x, y))];
+
+		let bottom = value(base.x, base.y) * (1.0 - t.x) + value(next.x, base.y) * t.x;
+		let top = value(base.x, next.y) * (1.0 - t.x) + value(next.x, next.y) * t.x;
+		bottom * (1.0 - t.y) + top * t.y
+	}
+
+	pub fn value(&self, index: usize) -> f32 {
+		self.values[index]
+	}
+
+	pub fn value_mut(&mut self, index: usize) -> &mut f32 {
+		&mut self.values[index]
+	}
+
+	pub fn values_mut(&mut self) -> &mut [f32] {
+		&mut self.values
+	}
+
+	pub fn diffuse(&mut self, diffusion_rate: f32, dt: f32) {
+		// Explicit diffusion is only stable up to a certain step size, so larger steps are clamped
+		// rather than allowed to oscillate.
+		let factor = (diffusion_rate * dt / (self.tile_size * self.tile_size))
+			.min(Vec2::splat(MAX_DIFFUSION_FACTOR));
+		if factor.max_element() <= 0.0 {
+			return;
+		}
+
+		let width = self.dimensions.x as usize;
+		let height = self.dimensions.y as usize;
+		// Tiles at the edge of a bounded field see themselves beyond it, so nothing flows out,
+		// while a periodic field exchanges with the tiles on the opposite edge.
+		let periodic = self.periodic;
+		let previous = |position: usize, length: usize| match position {
+			0 if periodic => Some(length - 1),
+			0 => None,
+			_ => Some(position - 1)
+		};
+		let next = |position: usize, length: usize| match position + 1 {
+			next if next < length => Some(next),
+			_ if periodic => Some(0),
+			_ => None
+		};
+		self.scratch.clear();
+		self.scratch.extend_from_slice(&self.values);
+		for y in 0..height {
+			for x in 0..width {
+				let index = y * width + x;
+				let value = self.scratch[index];
+				let at = |x: Option<usize>, y: Option<usize>| match (x, y) {
+					(Some(x), Some(y)) => self.scratch[y * width + x],
+					_ => value
+				};
+				let left = at(previous(x, width), Some(y));
+				let right = at(next(x, width), Some(y));
+				let down = at(Some(x), previous(y, height));
+				let up = at(Some(x), next(y, height));
+				self.values[index] = value
+					+ factor.x * (left + right - 2.0 * value)
+					+ factor.y * (down + up - 2.0 * value);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn torus_field() -> ScalarField {
+		let mut field = ScalarField::periodic(Vec2::splat(100.0), 10.0, 0.0);
+		for (index, value) in field.values_mut().iter_mut().enumerate() {
+			*value = index as f32;
+		}
+		field
+	}
+
+	#[test]
+	fn periodic_tile_index_wraps() {
+		let field = torus_field();
+		assert_eq!(field.tile_index(Vec2::new(-45.0, -45.0)), Some(0));
+		assert_eq!(field.tile_index(Vec2::new(55.0, 55.0)), Some(0));
+		assert_eq!(field.tile_index(Vec2::new(-55.0, -45.0)), Some(9));
+	}
+
+	#[test]
+	fn periodic_sample_is_continuous_across_the_seam() {
+		let field = torus_field();
+		for y in [-40.0, 0.0, 33.0] {
+			let left = field.sample(Vec2::new(-50.0 + 1e-3, y));
+			let right = field.sample(Vec2::new(50.0 - 1e-3, y));
+			assert!((left - right).abs() < 0.01, "{left} != {right}");
+		}
+	}
+
+	#[test]
+	fn periodic_circle_reaches_across_the_seam() {
+		let field = torus_field();
+		let tiles: Vec<usize> = field.tiles_in_circle(Vec2::new(49.0, 5.0), 7.0).collect();
+		assert!(tiles.contains(&field.tile_index(Vec2::new(48.0, 5.0)).unwrap()));
+		assert!(tiles.contains(&field.tile_index(Vec2::new(-48.0, 5.0)).unwrap()));
+
+		let everything: Vec<usize> = field.tiles_in_circle(Vec2::ZERO, 500.0).collect();
+		assert_eq!(everything.len(), 100);
+	}
+
+	#[test]
+	fn periodic_diffusion_conserves_mass() {
+		let mut field = ScalarField::periodic(Vec2::splat(100.0), 10.0, 0.0);
+		*field.value_mut(0) = 1.0;
+		for _ in 0..10 {
+			field.diffuse(100.0, 0.1);
+		}
+		let total: f32 = field.values_mut().iter().sum();
+		assert!((total - 1.0).abs() < 1e-5);
+		assert_eq!(field.value(9), field.value(1));
+		assert_eq!(field.value(90), field.value(10));
+	}
+}
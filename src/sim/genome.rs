@@ -0,0 +1,329 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::rand::Rng;
+
+use super::{
+	brain::{self, Network},
+	chemicals::ChemicalConfig,
+	receptors::{
+		adhesion::AdhesionReceptor, attract::AttractionReceptor, brain::BrainReceptor,
+		chemotaxis::ChemotaxisReceptor, emission::EmissionReceptor, predation::PredationReceptor,
+		tumble::RunAndTumbleReceptor, Receptor
+	}
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceptorGene {
+	Attraction { strength: Vec3 },
+	Predation { strength: f32 },
+	Emission { channel: usize, rate: f32 },
+	Chemotaxis { channel: usize, strength: f32 },
+	RunAndTumble { channel: usize, speed: f32 },
+	Brain(Network),
+	Adhesion { strength: f32 }
+}
+
+const MAX_INITIAL_ATTRACTION: f32 = 50.0;
+const MAX_INITIAL_PREDATION: f32 = 1.0;
+const MAX_INITIAL_EMISSION: f32 = 1.0;
+const MAX_INITIAL_CHEMOTAXIS: f32 = 1.0;
+const MAX_INITIAL_RUN_SPEED: f32 = 1.0;
+const MAX_INITIAL_ADHESION: f32 = 1.0;
+
+impl ReceptorGene {
+	pub fn build(&self) -> Box<dyn Receptor> {
+		match self {
+			Self::Attraction { strength } => Box::new(AttractionReceptor::new(*strength)),
+			Self::Predation { strength } => Box::new(PredationReceptor::new(*strength)),
+			Self::Emission { channel, rate } => Box::new(EmissionReceptor::new(*channel, *rate)),
+			Self::Chemotaxis { channel, strength } => {
+				Box::new(ChemotaxisReceptor::new(*channel, *strength))
+			}
+			Self::RunAndTumble { channel, speed } => {
+				Box::new(RunAndTumbleReceptor::new(*channel, *speed))
+			}
+			Self::Brain(network) => Box::new(BrainReceptor::new(network.clone())),
+			Self::Adhesion { strength } => Box::new(AdhesionReceptor::new(*strength))
+		}
+	}
+
+	fn random(rng: &mut Rng, config: &MutationConfig) -> Self {
+		let channels = config.chemical_channels.max(1);
+		match rng.rand_index(7) {
+			0 => {
+				let mut random_strength =
+					|| rng.rand_in_range(-MAX_INITIAL_ATTRACTION, MAX_INITIAL_ATTRACTION);
+				Self::Attraction {
+					strength: Vec3::new(random_strength(), random_strength(), random_strength())
+				}
+			}
+			1 => Self::Predation {
+				strength: rng.rand_in_range(0.0, MAX_INITIAL_PREDATION)
+			},
+			2 => Self::Emission {
+				channel: rng.rand_index(channels),
+				rate: rng.rand_in_range(0.0, MAX_INITIAL_EMISSION)
+			},
+			3 => Self::Chemotaxis {
+				channel: rng.rand_index(channels),
+				strength: rng.rand_in_range(-MAX_INITIAL_CHEMOTAXIS, MAX_INITIAL_CHEMOTAXIS)
+			},
+			4 => Self::RunAndTumble {
+				channel: rng.rand_index(channels),
+				speed: rng.rand_in_range(0.0, MAX_INITIAL_RUN_SPEED)
+			},
+			5 => Self::Brain(Network::random(rng, config.chemical_channels)),
+			_ => Self::Adhesion {
+				strength: rng.rand_in_range(0.0, MAX_INITIAL_ADHESION)
+			}
+		}
+	}
+
+	fn jitter(&mut self, rng: &mut Rng, config: &MutationConfig) {
+		match self {
+			Self::Attraction { strength } => {
+				*strength += jitter_vec3(rng, config.parameter_jitter * MAX_INITIAL_ATTRACTION);
+			}
+			Self::Predation { strength } => {
+				let jitter = config.parameter_jitter * MAX_INITIAL_PREDATION;
+				*strength = (*strength + rng.rand_with_spread(0.0, jitter)).max(0.0);
+			}
+			Self::Emission { rate, .. } => {
+				let jitter = config.parameter_jitter * MAX_INITIAL_EMISSION;
+				*rate = (*rate + rng.rand_with_spread(0.0, jitter)).max(0.0);
+			}
+			Self::Chemotaxis { strength, .. } => {
+				*strength +=
+					rng.rand_with_spread(0.0, config.parameter_jitter * MAX_INITIAL_CHEMOTAXIS);
+			}
+			Self::RunAndTumble { speed, .. } => {
+				let jitter = config.parameter_jitter * MAX_INITIAL_RUN_SPEED;
+				*speed = (*speed + rng.rand_with_spread(0.0, jitter)).max(0.0);
+			}
+			Self::Brain(network) => {
+				let jitter = config.parameter_jitter * brain::MAX_INITIAL_WEIGHT;
+				for weight in network.weights_mut() {
+					*weight += rng.rand_with_spread(0.0, jitter);
+				}
+			}
+			Self::Adhesion { strength } => {
+				let jitter = config.parameter_jitter * MAX_INITIAL_ADHESION;
+				*strength = (*strength + rng.rand_with_spread(0.0, jitter)).max(0.0);
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MutationConfig {
+	pub parameter_rate: f32,
+	pub parameter_jitter: f32,
+	pub color_jitter: f32,
+	pub size_jitter: f32,
+	pub insertion_rate: f32,
+	pub deletion_rate: f32,
+	pub max_receptors: usize,
+	pub chemical_channels: usize
+}
+
+impl Default for MutationConfig {
+	fn default() -> Self {
+		Self {
+			parameter_rate: 0.2,
+			parameter_jitter: 0.1,
+			color_jitter: 0.05,
+			size_jitter: 0.05,
+			insertion_rate: 0.02,
+			deletion_rate: 0.02,
+			max_receptors: 8,
+			chemical_channels: ChemicalConfig::default().channels
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Genome {
+	pub color: Vec3,
+	pub size: f32,
+	pub receptors: Vec<ReceptorGene>
+}
+
+impl Genome {
+	pub fn new(color: Vec3, size: f32, receptors: Vec<ReceptorGene>) -> Self {
+		Self {
+			color,
+			size,
+			receptors
+		}
+	}
+
+	pub fn mutate(&mut self, rng: &mut Rng, config: &MutationConfig) {
+		self.color =
+			(self.color + jitter_vec3(rng, config.color_jitter)).clamp(Vec3::ZERO, Vec3::ONE);
+		self.size *= 1.0 + rng.rand_with_spread(0.0, config.size_jitter);
+
+		for gene in &mut self.receptors {
+			if rng.rand() < config.parameter_rate {
+				gene.jitter(rng, config);
+			}
+		}
+		if !self.receptors.is_empty() && rng.rand() < config.deletion_rate {
+			let index = rng.rand_index(self.receptors.len());
+			self.receptors.remove(index);
+		}
+		if self.receptors.len() < config.max_receptors && rng.rand() < config.insertion_rate {
+			self.receptors.push(ReceptorGene::random(rng, config));
+		}
+	}
+
+	pub fn crossover(&self, other: &Genome, rng: &mut Rng) -> Genome {
+		let color_mix = Vec3::new(rng.rand(), rng.rand(), rng.rand());
+		let num_receptors = if rng.rand() < 0.5 {
+			self.receptors.len()
+		} else {
+			other.receptors.len()
+		};
+		let receptors = (0..num_receptors)
+			.filter_map(|index| {
+				let (first, second) = if rng.rand() < 0.5 {
+					(self, other)
+				} else {
+					(other, self)
+				};
+				first
+					.receptors
+					.get(index)
+					.or_else(|| second.receptors.get(index))
+					.cloned()
+			})
+			.collect();
+
+		Genome {
+			color: self.color + (other.color - self.color) * color_mix,
+			size: if rng.rand() < 0.5 {
+				self.size
+			} else {
+				other.size
+			},
+			receptors
+		}
+	}
+
+	pub fn adhesion(&self) -> f32 {
+		self.receptors
+			.iter()
+			.map(|gene| match gene {
+				ReceptorGene::Adhesion { strength } => *strength,
+				_ => 0.0
+			})
+			.fold(0.0, f32::max)
+	}
+
+	pub fn build_receptors(&self) -> Vec<Box<dyn Receptor>> {
+		self.receptors.iter().map(ReceptorGene::build).collect()
+	}
+}
+
+fn jitter_vec3(rng: &mut Rng, spread: f32) -> Vec3 {
+	Vec3::new(
+		rng.rand_with_spread(0.0, spread),
+		rng.rand_with_spread(0.0, spread),
+		rng.rand_with_spread(0.0, spread)
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn random_genome(rng: &mut Rng, config: &MutationConfig) -> Genome {
+		let receptors = (0..4).map(|_| ReceptorGene::random(rng, config)).collect();
+		Genome::new(Vec3::new(0.2, 0.5, 0.8), 6.0, receptors)
+	}
+
+	#[test]
+	fn mutation_is_deterministic_for_a_seed() {
+		let config = MutationConfig::default();
+		let mutated = |seed| {
+			let mut rng = Rng::new(seed);
+			let mut genome = random_genome(&mut Rng::new(1), &config);
+			for _ in 0..50 {
+				genome.mutate(&mut rng, &config);
+			}
+			genome
+		};
+		assert_eq!(mutated(3), mutated(3));
+		assert_ne!(mutated(3), mutated(4));
+	}
+
+	#[test]
+	fn mutation_stays_within_range() {
+		let config = MutationConfig {
+			parameter_rate: 1.0,
+			color_jitter: 0.5,
+			size_jitter: 0.2,
+			insertion_rate: 0.5,
+			deletion_rate: 0.1,
+			max_receptors: 5,
+			..MutationConfig::default()
+		};
+		let mut rng = Rng::new(2);
+		let mut genome = random_genome(&mut rng, &config);
+		for _ in 0..500 {
+			let size = genome.size;
+			genome.mutate(&mut rng, &config);
+
+			let growth = genome.size / size;
+			assert!((0.9..=1.1).contains(&growth), "size changed by {growth}");
+			assert!(genome.color.cmpge(Vec3::ZERO).all() && genome.color.cmple(Vec3::ONE).all());
+			assert!(genome.receptors.len() <= config.max_receptors);
+			for gene in &genome.receptors {
+				match *gene {
+					ReceptorGene::Predation { strength: value }
+					| ReceptorGene::Emission { rate: value, .. }
+					| ReceptorGene::RunAndTumble { speed: value, .. }
+					| ReceptorGene::Adhesion { strength: value } => assert!(value >= 0.0),
+					_ => ()
+				}
+				if let ReceptorGene::Emission { channel, .. }
+				| ReceptorGene::Chemotaxis { channel, .. }
+				| ReceptorGene::RunAndTumble { channel, .. } = *gene
+				{
+					assert!(channel < config.chemical_channels);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn crossover_takes_each_gene_from_a_parent() {
+		let config = MutationConfig::default();
+		let mut rng = Rng::new(5);
+		let first = random_genome(&mut rng, &config);
+		let mut second = random_genome(&mut rng, &config);
+		second.color = Vec3::new(0.9, 0.1, 0.4);
+		second.size = 3.0;
+		second.receptors.truncate(2);
+
+		for _ in 0..50 {
+			let child = first.crossover(&second, &mut rng);
+			assert!(child.size == first.size || child.size == second.size);
+			let (min, max) = (first.color.min(second.color), first.color.max(second.color));
+			assert!(child.color.cmpge(min).all() && child.color.cmple(max).all());
+			assert!(
+				child.receptors.len() == first.receptors.len()
+					|| child.receptors.len() == second.receptors.len()
+			);
+			for (index, gene) in child.receptors.iter().enumerate() {
+				assert!(
+					first.receptors.get(index) == Some(gene)
+						|| second.receptors.get(index) == Some(gene),
+					"gene {index} comes from neither parent"
+				);
+			}
+		}
+	}
+}
@@ -0,0 +1,269 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::cell::Cell;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineageNode {
+	pub parent: Option<Uuid>,
+	pub generation: u32,
+	pub birth_tick: u64,
+	pub death_tick: Option<u64>,
+	pub children: Vec<Uuid>
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lineage {
+	nodes: BTreeMap<Uuid, LineageNode>
+}
+
+enum Step {
+	Enter(Uuid),
+	Exit(Uuid),
+	Separator
+}
+
+impl Lineage {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn nodes(&self) -> &BTreeMap<Uuid, LineageNode> {
+		&self.nodes
+	}
+
+	pub fn record_birth(&mut self, cell: &Cell) {
+		let parent = cell.parent.filter(|parent| self.nodes.contains_key(parent));
+		if let Some(parent) = parent {
+			self.nodes.get_mut(&parent).unwrap().children.push(cell.id);
+		}
+		self.nodes.insert(
+			cell.id,
+			LineageNode {
+				parent,
+				generation: cell.generation,
+				birth_tick: cell.birth_tick,
+				death_tick: None,
+				children: Vec::new()
+			}
+		);
+	}
+
+	/// Records the death of a cell, at the tick stored in its `death_tick`.
+	pub fn record_death(&mut self, cell: &Cell) {
+		let (Some(node), Some(tick)) = (self.nodes.get_mut(&cell.id), cell.death_tick) else {
+			return;
+		};
+		node.death_tick = Some(tick);
+		self.prune(cell.id);
+	}
+
+	/// Drops lineages that have died out and splices out dead ancestors with a single child, so
+	/// the tree only grows with the number of branch points among living cells.
+	fn prune(&mut self, id: Uuid) {
+		let mut current = Some(id);
+		while let Some(id) = current {
+			let node = &self.nodes[&id];
+			if node.death_tick.is_none() {
+				return;
+			}
+			current = node.parent;
+			match node.children.len() {
+				0 => {
+					self.nodes.remove(&id);
+					if let Some(parent) = current {
+						let siblings = &mut self.nodes.get_mut(&parent).unwrap().children;
+						siblings.retain(|sibling| *sibling != id);
+					}
+				}
+				1 => {
+					let child = node.children[0];
+					self.nodes.remove(&id);
+					self.nodes.get_mut(&child).unwrap().parent = current;
+					if let Some(parent) = current {
+						for sibling in &mut self.nodes.get_mut(&parent).unwrap().children {
+							if *sibling == id {
+								*sibling = child;
+							}
+						}
+					}
+					return;
+				}
+				_ => return
+			}
+		}
+	}
+
+	pub fn roots(&self) -> impl Iterator<Item = Uuid> + '_ {
+		self.nodes
+			.iter()
+			.filter(|(_, node)| node.parent.is_none())
+			.map(|(id, _)| *id)
+	}
+
+	fn branch_length(&self, id: Uuid, current_tick: u64) -> u64 {
+		let node = &self.nodes[&id];
+		let end = node.death_tick.unwrap_or(current_tick);
+		let start = node
+			.parent
+			.and_then(|parent| self.nodes[&parent].death_tick)
+			.unwrap_or(node.birth_tick);
+		end.saturating_sub(start)
+	}
+
+	/// Branch lengths are measured in ticks. Multiple founding cells are joined under an unnamed
+	/// root.
+	pub fn to_newick(&self, current_tick: u64) -> String {
+		let roots: Vec<Uuid> = self.roots().collect();
+		let mut output = String::new();
+		let mut steps = Vec::new();
+		Self::push_children(&mut steps, &roots);
+
+		if roots.len() > 1 {
+			output.push('(');
+		}
+		while let Some(step) = steps.pop() {
+			match step {
+				Step::Enter(id) => {
+					let children = &self.nodes[&id].children;
+					if children.is_empty() {
+						write!(output, "{id}:{}", self.branch_length(id, current_tick)).unwrap();
+					} else {
+						output.push('(');
+						steps.push(Step::Exit(id));
+						Self::push_children(&mut steps, children);
+					}
+				}
+				Step::Exit(id) => {
+					write!(output, "){id}:{}", self.branch_length(id, current_tick)).unwrap();
+				}
+				Step::Separator => output.push(',')
+			}
+		}
+		if roots.len() > 1 {
+			output.push(')');
+		}
+		output.push(';');
+		output
+	}
+
+	fn push_children(steps: &mut Vec<Step>, children: &[Uuid]) {
+		for (index, child) in children.iter().enumerate().rev() {
+			steps.push(Step::Enter(*child));
+			if index > 0 {
+				steps.push(Step::Separator);
+			}
+		}
+	}
+
+	pub fn to_graphml(&self) -> String {
+		let mut output = String::new();
+		output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+		output.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+		for (key, kind) in [
+			("generation", "int"),
+			("birth_tick", "long"),
+			("death_tick", "long")
+		] {
+			writeln!(
+				output,
+				"  <key id=\"{key}\" for=\"node\" attr.name=\"{key}\" attr.type=\"{kind}\"/>"
+			)
+			.unwrap();
+		}
+		output.push_str("  <graph id=\"lineage\" edgedefault=\"directed\">\n");
+		for (id, node) in &self.nodes {
+			writeln!(output, "    <node id=\"{id}\">").unwrap();
+			writeln!(
+				output,
+				"      <data key=\"generation\">{}</data>",
+				node.generation
+			)
+			.unwrap();
+			writeln!(
+				output,
+				"      <data key=\"birth_tick\">{}</data>",
+				node.birth_tick
+			)
+			.unwrap();
+			if let Some(death_tick) = node.death_tick {
+				writeln!(output, "      <data key=\"death_tick\">{death_tick}</data>").unwrap();
+			}
+			output.push_str("    </node>\n");
+		}
+		for (id, node) in &self.nodes {
+			if let Some(parent) = node.parent {
+				writeln!(output, "    <edge source=\"{parent}\" target=\"{id}\"/>").unwrap();
+			}
+		}
+		output.push_str("  </graph>\n</graphml>\n");
+		output
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glam::Vec2;
+
+	use super::*;
+
+	fn birth(lineage: &mut Lineage, id: u128, parent: Option<u128>, tick: u64) -> Uuid {
+		let mut cell = Cell::test_cell(id, 1.0, Vec2::ZERO);
+		cell.parent = parent.map(Uuid::from_u128);
+		cell.birth_tick = tick;
+		lineage.record_birth(&cell);
+		cell.id
+	}
+
+	fn death(lineage: &mut Lineage, id: Uuid, tick: u64) {
+		let mut cell = Cell::test_cell(id.as_u128(), 1.0, Vec2::ZERO);
+		cell.death_tick = Some(tick);
+		lineage.record_death(&cell);
+	}
+
+	#[test]
+	fn newick_prunes_extinct_branches_and_splices_single_children() {
+		let mut lineage = Lineage::new();
+		let root = birth(&mut lineage, 1, None, 0);
+		let extinct = birth(&mut lineage, 2, None, 0);
+		death(&mut lineage, extinct, 5);
+
+		// The root divides at tick 10 and its first daughter at tick 20. As in the simulation,
+		// daughters are recorded before their parent's death.
+		let first = birth(&mut lineage, 3, Some(1), 10);
+		let second = birth(&mut lineage, 4, Some(1), 10);
+		death(&mut lineage, root, 10);
+		let dead_end = birth(&mut lineage, 5, Some(3), 20);
+		let survivor = birth(&mut lineage, 6, Some(3), 20);
+		death(&mut lineage, first, 20);
+		death(&mut lineage, dead_end, 30);
+
+		// The extinct founder and the dead end are dropped, leaving the first daughter with one
+		// descendant, which takes its place.
+		assert!(!lineage.nodes().contains_key(&extinct));
+		assert!(!lineage.nodes().contains_key(&dead_end));
+		assert!(!lineage.nodes().contains_key(&first));
+		assert_eq!(lineage.nodes()[&survivor].parent, Some(root));
+		assert_eq!(lineage.nodes()[&root].children, vec![survivor, second]);
+		assert_eq!(
+			lineage.to_newick(40),
+			format!("({survivor}:30,{second}:30){root}:10;")
+		);
+
+		// Once every descendant has died the whole tree is gone.
+		death(&mut lineage, survivor, 45);
+		death(&mut lineage, second, 50);
+		assert!(lineage.nodes().is_empty());
+		assert_eq!(lineage.to_newick(60), ";");
+	}
+
+	#[test]
+	fn multiple_founders_share_an_unnamed_root() {
+		let mut lineage = Lineage::new();
+		let first = birth(&mut lineage, 1, None, 0);
+		let second = birth(&mut lineage, 2, None, 5);
+		assert_eq!(lineage.to_newick(10), format!("({first}:10,{second}:5);"));
+	}
+}
@@ -1,5 +1,22 @@
+pub mod bonds;
+pub mod bounds;
+pub mod brain;
 pub mod cell;
+pub mod chemicals;
+pub mod clock;
+pub mod environment;
+pub mod events;
+pub mod field;
+pub mod genome;
+pub mod lineage;
+pub mod nutrients;
+pub mod obstacles;
+pub mod quadtree;
 pub mod receptors;
+pub mod scenario;
 mod simulation;
+pub mod snapshot;
+pub mod spatial;
+pub mod stats;
 
 pub use simulation::*;
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+	bounds::WorldBounds,
+	cell::Cell,
+	field::{FieldConfig, ScalarField}
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NutrientConfig {
+	pub field: FieldConfig,
+	pub capacity: f32,
+	pub regrowth_rate: f32,
+	pub absorption_rate: f32,
+	pub energy_per_nutrient: f64
+}
+
+impl Default for NutrientConfig {
+	fn default() -> Self {
+		Self {
+			field: FieldConfig::default(),
+			capacity: 1.0,
+			regrowth_rate: 0.05,
+			absorption_rate: 0.5,
+			energy_per_nutrient: 1.0
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NutrientField {
+	config: NutrientConfig,
+	field: ScalarField,
+	#[serde(skip)]
+	demand: Vec<f32>
+}
+
+impl NutrientField {
+	pub fn new(config: NutrientConfig, bounds: &WorldBounds) -> Self {
+		Self {
+			config,
+			field: config.field.build(bounds, config.capacity),
+			demand: Vec::new()
+		}
+	}
+
+	pub fn config(&self) -> NutrientConfig {
+		self.config
+	}
+
+	pub fn tick(&mut self, dt: f64) {
+		let dt = dt as f32;
+		let NutrientConfig {
+			field,
+			capacity,
+			regrowth_rate,
+			..
+		} = self.config;
+
+		for value in self.field.values_mut() {
+			*value += regrowth_rate * (capacity - *value) * dt;
+		}
+		self.field.diffuse(field.diffusion_rate, dt);
+	}
+
+	pub fn feed<'a>(&mut self, cells: impl Iterator<Item = &'a mut Cell>, dt: f64) {
+		// Cells competing for the same tile split what is there in proportion to what they ask for,
+		// so the order cells are fed in doesn't matter.
+		let wanted = self.config.absorption_rate * dt as f32;
+		if wanted <= 0.0 {
+			return;
+		}
+		let mut cells: Vec<&mut Cell> = cells.collect();
+
+		self.demand.clear();
+		self.demand.resize(self.field.values_mut().len(), 0.0);
+		for cell in &cells {
+			for index in self.field.tiles_in_circle(cell.position, cell.size) {
+				self.demand[index] += wanted;
+			}
+		}
+
+		for cell in &mut cells {
+			let absorbed: f32 = self
+				.field
+				.tiles_in_circle(cell.position, cell.size)
+				.map(|index| wanted * (self.field.value(index) / self.demand[index]).min(1.0))
+				.sum();
+			cell.energy += absorbed as f64 * self.config.energy_per_nutrient;
+		}
+
+		for (index, demand) in self.demand.iter().enumerate() {
+			let value = self.field.value_mut(index);
+			*value = (*value - demand).max(0.0);
+		}
+	}
+}
@@ -0,0 +1,339 @@
+use std::f32::consts::TAU;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use super::{bounds::WorldBounds, cell::Cell};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Obstacle {
+	Circle { center: Vec2, radius: f32 },
+	Segment { start: Vec2, end: Vec2 },
+	Polygon { vertices: Vec<Vec2> }
+}
+
+struct Contact {
+	normal: Vec2,
+	penetration: f32
+}
+
+const RESTITUTION: f32 = 0.5;
+const CIRCLE_RESOLUTION: usize = 48;
+const SEGMENT_WIDTH: f32 = 4.0;
+/// Distance a swept cell is kept back from the point where its path crosses an obstacle, so that
+/// it stays on the side it came from.
+const SWEEP_BACKOFF: f32 = 0.01;
+
+impl Obstacle {
+	/// Pushes a cell out of the obstacle. In a toroidal world the cell is also checked against the
+	/// copies of the obstacle across the seam.
+	pub fn collide(&self, cell: &mut Cell, bounds: &WorldBounds) {
+		for offset in image_offsets(bounds, cell.position, cell.size) {
+			cell.position += offset;
+			self.collide_image(cell);
+			cell.position -= offset;
+		}
+	}
+
+	/// Resolves a cell that moved from `previous` to its current position within one step, against
+	/// every copy of the obstacle the cell may have reached.
+	pub fn sweep(&self, cell: &mut Cell, previous: Vec2, bounds: &WorldBounds) {
+		let range = cell.size + cell.position.distance(previous);
+		for offset in image_offsets(bounds, cell.position, range) {
+			cell.position += offset;
+			self.sweep_image(cell, previous + offset);
+			cell.position -= offset;
+		}
+	}
+
+	fn collide_image(&self, cell: &mut Cell) {
+		let Some(Contact {
+			normal,
+			penetration
+		}) = self.contact(cell.position, cell.size)
+		else {
+			return;
+		};
+		cell.position += penetration * normal;
+		let normal_velocity = cell.velocity.dot(normal);
+		if normal_velocity < 0.0 {
+			cell.velocity -= (1.0 + RESTITUTION) * normal_velocity * normal;
+		}
+	}
+
+	/// A fast cell could pass through the obstacle without ever overlapping it, so it is first
+	/// stopped where its path crosses the obstacle's outline.
+	fn sweep_image(&self, cell: &mut Cell, previous: Vec2) {
+		if let Some(t) = self.first_crossing(previous, cell.position) {
+			let path = cell.position - previous;
+			let t = (t - SWEEP_BACKOFF / path.length()).max(0.0);
+			cell.position = previous + t * path;
+		}
+		self.collide_image(cell);
+	}
+
+	/// Fraction of the way from `start` to `end` at which the path first crosses the outline.
+	fn first_crossing(&self, start: Vec2, end: Vec2) -> Option<f32> {
+		match self {
+			Self::Circle { center, radius } => circle_crossing(start, end, *center, *radius),
+			Self::Segment {
+				start: segment_start,
+				end: segment_end
+			} => segment_crossing(start, end, *segment_start, *segment_end),
+			Self::Polygon { vertices } => edges(vertices)
+				.filter_map(|(edge_start, edge_end)| {
+					segment_crossing(start, end, edge_start, edge_end)
+				})
+				.min_by(f32::total_cmp)
+		}
+	}
+
+	fn contact(&self, position: Vec2, radius: f32) -> Option<Contact> {
+		let (normal, penetration) = match self {
+			Self::Circle {
+				center,
+				radius: obstacle_radius
+			} => {
+				let difference = position - *center;
+				let normal = difference.try_normalize().unwrap_or(Vec2::X);
+				(normal, radius + obstacle_radius - difference.length())
+			}
+			Self::Segment { start, end } => {
+				let closest = closest_point_on_segment(position, *start, *end);
+				let difference = position - closest;
+				let normal = difference
+					.try_normalize()
+					.or_else(|| (*end - *start).perp().try_normalize())
+					.unwrap_or(Vec2::X);
+				(normal, radius - difference.length())
+			}
+			Self::Polygon { vertices } => {
+				let closest = edges(vertices)
+					.map(|(start, end)| closest_point_on_segment(position, start, end))
+					.min_by(|a, b| {
+						a.distance_squared(position)
+							.total_cmp(&b.distance_squared(position))
+					})?;
+				let difference = position - closest;
+				let normal = difference.try_normalize().unwrap_or(Vec2::X);
+				if contains(vertices, position) {
+					(-normal, radius + difference.length())
+				} else {
+					(normal, radius - difference.length())
+				}
+			}
+		};
+		(penetration > 0.0).then_some(Contact {
+			normal,
+			penetration
+		})
+	}
+
+	pub fn outline(&self) -> Vec<Vec2> {
+		match self {
+			Self::Circle { center, radius } => (0..CIRCLE_RESOLUTION)
+				.map(|i| {
+					*center + *radius * Vec2::from_angle(i as f32 * TAU / CIRCLE_RESOLUTION as f32)
+				})
+				.collect(),
+			Self::Segment { start, end } => {
+				let offset = (*end - *start).perp().normalize_or_zero() * SEGMENT_WIDTH / 2.0;
+				vec![
+					*start - offset,
+					*end - offset,
+					*end + offset,
+					*start + offset,
+				]
+			}
+			Self::Polygon { vertices } => vertices.clone()
+		}
+	}
+}
+
+/// Offsets that move a cell next to the copies of the world it can reach within `range`, as used
+/// by the neighbourhood search. Beyond half the world's size every neighbouring copy is checked.
+fn image_offsets(bounds: &WorldBounds, position: Vec2, range: f32) -> Vec<Vec2> {
+	if let Some(offsets) = bounds.image_offsets(position, Some(range)) {
+		return offsets;
+	}
+	let WorldBounds::Torus { size } = *bounds else {
+		return vec![Vec2::ZERO];
+	};
+	(-1..=1)
+		.flat_map(|y| (-1..=1).map(move |x| Vec2::new(x as f32, y as f32) * size))
+		.collect()
+}
+
+fn edges(vertices: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+	vertices
+		.iter()
+		.zip(vertices.iter().cycle().skip(1))
+		.map(|(start, end)| (*start, *end))
+}
+
+fn closest_point_on_segment(position: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+	let direction = end - start;
+	let length_squared = direction.length_squared();
+	if length_squared == 0.0 {
+		return start;
+	}
+	let t = ((position - start).dot(direction) / length_squared).clamp(0.0, 1.0);
+	start + t * direction
+}
+
+fn segment_crossing(start: Vec2, end: Vec2, segment_start: Vec2, segment_end: Vec2) -> Option<f32> {
+	let path = end - start;
+	let segment = segment_end - segment_start;
+	let denominator = path.perp_dot(segment);
+	if denominator == 0.0 {
+		return None;
+	}
+	let offset = segment_start - start;
+	let t = offset.perp_dot(segment) / denominator;
+	let u = offset.perp_dot(path) / denominator;
+	((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(t)
+}
+
+fn circle_crossing(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+	let path = end - start;
+	let offset = start - center;
+	let a = path.length_squared();
+	if a == 0.0 {
+		return None;
+	}
+	let b = 2.0 * offset.dot(path);
+	let c = offset.length_squared() - radius * radius;
+	let discriminant = b * b - 4.0 * a * c;
+	if discriminant < 0.0 {
+		return None;
+	}
+	let root = discriminant.sqrt();
+	[(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+		.into_iter()
+		.find(|t| (0.0..=1.0).contains(t))
+}
+
+fn contains(vertices: &[Vec2], position: Vec2) -> bool {
+	let mut inside = false;
+	for (start, end) in edges(vertices) {
+		if (start.y > position.y) != (end.y > position.y) {
+			let crossing = start.x + (position.y - start.y) / (end.y - start.y) * (end.x - start.x);
+			if position.x < crossing {
+				inside = !inside;
+			}
+		}
+	}
+	inside
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn moving_cell(position: Vec2, step: Vec2) -> Cell {
+		let mut cell = Cell::test_cell(0, 2.0, position);
+		cell.velocity = step * 120.0;
+		cell
+	}
+
+	fn assert_stopped(
+		obstacle: &Obstacle,
+		from: Vec2,
+		to: Vec2,
+		expected_side: impl Fn(Vec2) -> bool
+	) {
+		let mut cell = moving_cell(to, to - from);
+		obstacle.sweep(&mut cell, from, &WorldBounds::Open);
+		assert!(
+			expected_side(cell.position),
+			"passed through to {}",
+			cell.position
+		);
+		let contact = obstacle.contact(cell.position, cell.size);
+		assert!(contact.is_none_or(|contact| contact.penetration < 1e-3));
+	}
+
+	#[test]
+	fn fast_cells_do_not_pass_through_segments() {
+		let segment = Obstacle::Segment {
+			start: Vec2::new(0.0, -50.0),
+			end: Vec2::new(0.0, 50.0)
+		};
+		assert_stopped(&segment, Vec2::new(-10.0, 0.0), Vec2::new(40.0, 3.0), |p| {
+			p.x < 0.0
+		});
+		assert_stopped(&segment, Vec2::new(10.0, 5.0), Vec2::new(-40.0, 0.0), |p| {
+			p.x > 0.0
+		});
+	}
+
+	#[test]
+	fn fast_cells_do_not_pass_through_circles_or_polygons() {
+		let circle = Obstacle::Circle {
+			center: Vec2::ZERO,
+			radius: 10.0
+		};
+		assert_stopped(&circle, Vec2::new(-20.0, 1.0), Vec2::new(20.0, 1.0), |p| {
+			p.x < 0.0
+		});
+
+		let square = Obstacle::Polygon {
+			vertices: vec![
+				Vec2::new(-5.0, -5.0),
+				Vec2::new(5.0, -5.0),
+				Vec2::new(5.0, 5.0),
+				Vec2::new(-5.0, 5.0),
+			]
+		};
+		assert_stopped(&square, Vec2::new(0.0, -20.0), Vec2::new(1.0, 20.0), |p| {
+			p.y < 0.0
+		});
+	}
+
+	#[test]
+	fn slow_cells_are_resolved_as_before() {
+		let segment = Obstacle::Segment {
+			start: Vec2::new(0.0, -50.0),
+			end: Vec2::new(0.0, 50.0)
+		};
+		let mut swept = moving_cell(Vec2::new(-1.0, 0.0), Vec2::new(2.0, 0.0));
+		let mut collided = swept.clone();
+		segment.sweep(&mut swept, Vec2::new(-3.0, 0.0), &WorldBounds::Open);
+		segment.collide(&mut collided, &WorldBounds::Open);
+		assert_eq!(swept.position, collided.position);
+		assert_eq!(swept.velocity, collided.velocity);
+	}
+
+	#[test]
+	fn obstacles_block_across_a_torus_seam() {
+		let bounds = WorldBounds::torus(Vec2::splat(200.0)).unwrap();
+		let circle = Obstacle::Circle {
+			center: Vec2::new(-97.0, 0.0),
+			radius: 5.0
+		};
+		// The cell overlaps the circle's copy on the other side of the seam at x = 100.
+		let mut cell = moving_cell(Vec2::new(99.0, 0.0), Vec2::ZERO);
+		circle.collide(&mut cell, &bounds);
+		assert!(cell.position.x < 96.5, "not pushed back: {}", cell.position);
+
+		// A fast cell crossing the seam stops at the copy of a wall just beyond it.
+		let segment = Obstacle::Segment {
+			start: Vec2::new(-95.0, -50.0),
+			end: Vec2::new(-95.0, 50.0)
+		};
+		let from = Vec2::new(95.0, 0.0);
+		let mut cell = moving_cell(Vec2::new(120.0, 0.0), Vec2::new(25.0, 0.0));
+		segment.sweep(&mut cell, from, &bounds);
+		assert!(
+			cell.position.x < 105.0,
+			"passed through to {}",
+			cell.position
+		);
+
+		// In a bounded world the far side of the world is out of reach.
+		let mut cell = moving_cell(Vec2::new(99.0, 0.0), Vec2::ZERO);
+		circle.collide(&mut cell, &WorldBounds::Open);
+		assert_eq!(cell.position, Vec2::new(99.0, 0.0));
+	}
+}
@@ -0,0 +1,298 @@
+use glam::{Vec2, Vec3};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Cluster {
+	pub count: usize,
+	pub mass: f32,
+	pub center_of_mass: Vec2,
+	pub color_mass: Vec3
+}
+
+impl Cluster {
+	const EMPTY: Self = Self {
+		count: 0,
+		mass: 0.0,
+		center_of_mass: Vec2::ZERO,
+		color_mass: Vec3::ZERO
+	};
+
+	fn merge(self, other: Self) -> Self {
+		let mass = self.mass + other.mass;
+		let center_of_mass = if mass > 0.0 {
+			(self.center_of_mass * self.mass + other.center_of_mass * other.mass) / mass
+		} else {
+			(self.center_of_mass + other.center_of_mass) / 2.0
+		};
+		Self {
+			count: self.count + other.count,
+			mass,
+			center_of_mass,
+			color_mass: self.color_mass + other.color_mass
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TreeEntry {
+	pub index: usize,
+	pub position: Vec2,
+	pub mass: f32,
+	pub color: Vec3
+}
+
+impl TreeEntry {
+	fn cluster(&self) -> Cluster {
+		Cluster {
+			count: 1,
+			mass: self.mass,
+			center_of_mass: self.position,
+			color_mass: self.color * self.mass
+		}
+	}
+}
+
+pub enum TreeItem {
+	Single(usize),
+	Cluster(Cluster)
+}
+
+#[derive(Debug)]
+struct Node {
+	min: Vec2,
+	size: f32,
+	cluster: Cluster,
+	children: Vec<usize>,
+	entries: Vec<TreeEntry>
+}
+
+impl Node {
+	fn contains(&self, position: Vec2) -> bool {
+		let max = self.min + Vec2::splat(self.size);
+		position.cmpge(self.min).all() && position.cmple(max).all()
+	}
+
+	fn distance_to(&self, position: Vec2) -> f32 {
+		let max = self.min + Vec2::splat(self.size);
+		position.clamp(self.min, max).distance(position)
+	}
+}
+
+#[derive(Debug)]
+pub struct QuadTree {
+	nodes: Vec<Node>
+}
+
+const LEAF_CAPACITY: usize = 4;
+const MAX_DEPTH: u32 = 24;
+
+impl QuadTree {
+	pub fn new(entries: Vec<TreeEntry>) -> Self {
+		let mut tree = Self { nodes: Vec::new() };
+		if entries.is_empty() {
+			return tree;
+		}
+
+		let (min, max) = entries.iter().fold(
+			(Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+			|(min, max), entry| (min.min(entry.position), max.max(entry.position))
+		);
+		let size = (max - min).max_element().max(f32::EPSILON);
+		tree.build_node(entries, min, size, 0);
+		tree
+	}
+
+	pub fn visit<F: FnMut(TreeItem)>(
+		&self,
+		position: Vec2,
+		range: Option<f32>,
+		opening_angle: f32,
+		exclude: usize,
+		visitor: &mut F
+	) {
+		if !self.nodes.is_empty() {
+			self.visit_node(0, position, range, opening_angle, exclude, visitor);
+		}
+	}
+
+	fn visit_node<F: FnMut(TreeItem)>(
+		&self,
+		index: usize,
+		position: Vec2,
+		range: Option<f32>,
+		opening_angle: f32,
+		exclude: usize,
+		visitor: &mut F
+	) {
+		let node = &self.nodes[index];
+		if range.is_some_and(|range| node.distance_to(position) >= range) {
+			return;
+		}
+
+		if node.children.is_empty() {
+			for entry in node.entries.iter().filter(|entry| entry.index != exclude) {
+				visitor(TreeItem::Single(entry.index));
+			}
+			return;
+		}
+
+		// A node containing the queried position may contain the querying cell itself, so it is
+		// never approximated as a whole.
+		let distance = node.cluster.center_of_mass.distance(position);
+		if !node.contains(position) && node.size < opening_angle * distance {
+			visitor(TreeItem::Cluster(node.cluster));
+			return;
+		}
+
+		for &child in &node.children {
+			self.visit_node(child, position, range, opening_angle, exclude, visitor);
+		}
+	}
+
+	fn build_node(&mut self, entries: Vec<TreeEntry>, min: Vec2, size: f32, depth: u32) -> usize {
+		let cluster = entries
+			.iter()
+			.fold(Cluster::EMPTY, |acc, entry| acc.merge(entry.cluster()));
+		let index = self.nodes.len();
+		self.nodes.push(Node {
+			min,
+			size,
+			cluster,
+			children: Vec::new(),
+			entries: Vec::new()
+		});
+
+		if entries.len() <= LEAF_CAPACITY || depth >= MAX_DEPTH {
+			self.nodes[index].entries = entries;
+			return index;
+		}
+
+		let half_size = size / 2.0;
+		let center = min + Vec2::splat(half_size);
+		let mut quadrants: [Vec<TreeEntry>; 4] = Default::default();
+		for entry in entries {
+			let quadrant = (entry.position.x >= center.x) as usize
+				+ 2 * (entry.position.y >= center.y) as usize;
+			quadrants[quadrant].push(entry);
+		}
+
+		for (quadrant, quadrant_entries) in quadrants.into_iter().enumerate() {
+			if quadrant_entries.is_empty() {
+				continue;
+			}
+			let offset = Vec2::new((quadrant % 2) as f32, (quadrant / 2) as f32) * half_size;
+			let child = self.build_node(quadrant_entries, min + offset, half_size, depth + 1);
+			self.nodes[index].children.push(child);
+		}
+		index
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rand::Rng;
+
+	fn random_entries(count: usize) -> Vec<TreeEntry> {
+		let mut rng = Rng::new(5);
+		(0..count)
+			.map(|index| TreeEntry {
+				index,
+				position: Vec2::new(
+					rng.rand_in_range(-200.0, 200.0),
+					rng.rand_in_range(-200.0, 200.0)
+				),
+				mass: rng.rand_in_range(0.5, 2.0),
+				color: Vec3::new(rng.rand(), rng.rand(), rng.rand())
+			})
+			.collect()
+	}
+
+	fn inverse_square(from: Vec2, to: Vec2, mass: f32) -> Vec2 {
+		let offset = to - from;
+		mass * offset / offset.length().powi(3)
+	}
+
+	/// Sums an inverse-square pull from everything the tree reports, along with how many entries
+	/// it covered.
+	fn tree_pull(tree: &QuadTree, entries: &[TreeEntry], of: usize) -> (Vec2, usize) {
+		let position = entries[of].position;
+		let mut force = Vec2::ZERO;
+		let mut count = 0;
+		tree.visit(position, None, 0.5, of, &mut |item| match item {
+			TreeItem::Single(index) => {
+				force += inverse_square(position, entries[index].position, entries[index].mass);
+				count += 1;
+			}
+			TreeItem::Cluster(cluster) => {
+				force += inverse_square(position, cluster.center_of_mass, cluster.mass);
+				count += cluster.count;
+			}
+		});
+		(force, count)
+	}
+
+	fn brute_force_pull(entries: &[TreeEntry], of: usize) -> Vec2 {
+		let position = entries[of].position;
+		entries
+			.iter()
+			.filter(|entry| entry.index != of)
+			.map(|entry| inverse_square(position, entry.position, entry.mass))
+			.sum()
+	}
+
+	#[test]
+	fn zero_opening_angle_visits_every_other_entry_once() {
+		let entries = random_entries(200);
+		let tree = QuadTree::new(entries.clone());
+		let mut seen = vec![0; entries.len()];
+		tree.visit(
+			entries[17].position,
+			None,
+			0.0,
+			17,
+			&mut |item| match item {
+				TreeItem::Single(index) => seen[index] += 1,
+				TreeItem::Cluster(_) => panic!("Nothing should be approximated")
+			}
+		);
+		for (index, count) in seen.into_iter().enumerate() {
+			assert_eq!(count, usize::from(index != 17), "entry {index}");
+		}
+	}
+
+	#[test]
+	fn barnes_hut_approximates_the_brute_force_sum() {
+		let entries = random_entries(500);
+		let tree = QuadTree::new(entries.clone());
+		for of in [0, 123, 499] {
+			let expected = brute_force_pull(&entries, of);
+			let (approximate, count) = tree_pull(&tree, &entries, of);
+			assert_eq!(count, entries.len() - 1);
+			let error = (approximate - expected).length() / expected.length();
+			assert!(error < 0.05, "relative error {error} for entry {of}");
+		}
+	}
+
+	#[test]
+	fn range_keeps_everything_within_it() {
+		let entries = random_entries(300);
+		let tree = QuadTree::new(entries.clone());
+		let position = entries[0].position;
+		let mut reported = Vec::new();
+		tree.visit(position, Some(50.0), 0.0, 0, &mut |item| {
+			if let TreeItem::Single(index) = item {
+				reported.push(index);
+			}
+		});
+		assert!(reported.len() < entries.len() - 1);
+		for entry in &entries[1..] {
+			if entry.position.distance(position) < 50.0 {
+				assert!(
+					reported.contains(&entry.index),
+					"entry {} missed",
+					entry.index
+				);
+			}
+		}
+	}
+}
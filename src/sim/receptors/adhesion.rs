@@ -0,0 +1,72 @@
+use glam::Vec2;
+use uuid::Uuid;
+
+use crate::sim::{cell::Cell, genome::ReceptorGene, quadtree::Cluster};
+
+use super::{Effect, InteractionAccumulator, Reach, Receptor};
+
+#[derive(Debug)]
+pub struct AdhesionReceptor {
+	strength: f32
+}
+
+impl AdhesionReceptor {
+	pub fn new(strength: f32) -> Self {
+		Self { strength }
+	}
+}
+
+struct AdhesionAccumulator<'a> {
+	receptor: &'a AdhesionReceptor,
+	touching: Vec<Uuid>,
+	efficiency: f32
+}
+
+const ADHESION_COST: f64 = 0.02;
+
+impl<'a> InteractionAccumulator for AdhesionAccumulator<'a> {
+	fn add_interaction(&mut self, cell: &Cell, other_cell: &Cell, _dt: f64) {
+		if cell.position.distance(other_cell.position) < cell.size + other_cell.size {
+			self.touching.push(other_cell.id);
+		}
+	}
+
+	fn add_cluster(&mut self, _cell: &Cell, _cluster: &Cluster, _dt: f64) {}
+
+	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2 {
+		let energy_cost = self.receptor.strength as f64 * ADHESION_COST * dt;
+		self.efficiency = cell.consume_energy(energy_cost);
+		Vec2::ZERO
+	}
+
+	fn effects(&mut self, cell: &Cell, effects: &mut Vec<Effect>) {
+		if self.efficiency < 1.0 || self.receptor.strength <= 0.0 {
+			return;
+		}
+		effects.extend(self.touching.drain(..).map(|other| Effect::Adhesion {
+			cell: cell.id,
+			other,
+			strength: self.receptor.strength
+		}));
+	}
+}
+
+impl Receptor for AdhesionReceptor {
+	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
+		Box::new(AdhesionAccumulator {
+			receptor: self,
+			touching: Vec::new(),
+			efficiency: 0.0
+		})
+	}
+
+	fn reach(&self) -> Reach {
+		Reach::Contact
+	}
+
+	fn gene(&self) -> Option<ReceptorGene> {
+		Some(ReceptorGene::Adhesion {
+			strength: self.strength
+		})
+	}
+}
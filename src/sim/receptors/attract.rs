@@ -1,10 +1,8 @@
-use std::sync::Mutex;
-
 use glam::{Vec2, Vec3};
 
-use crate::sim::cell::Cell;
+use crate::sim::{cell::Cell, genome::ReceptorGene, quadtree::Cluster};
 
-use super::{InteractionAccumulator, Receptor};
+use super::{InteractionAccumulator, Reach, Receptor};
 
 #[derive(Debug)]
 pub struct AttractionReceptor {
@@ -29,6 +27,21 @@ impl<'a> AttractionAccumulator<'a> {
 			force: Vec2::ZERO
 		}
 	}
+
+	fn add_attraction(&mut self, cell: &Cell, position: Vec2, color_mass: Vec3) {
+		let attraction = self.receptor.strength.dot(color_mass);
+		let pos_difference = position - cell.position;
+		let distance = pos_difference.length();
+		if distance >= ATTRACTION_RANGE {
+			return;
+		}
+
+		let Some(direction) = pos_difference.try_normalize() else {
+			return;
+		};
+		let force_strength = ATTRACTION_STRENGTH * attraction;
+		self.force += force_strength * direction;
+	}
 }
 
 const ATTRACTION_STRENGTH: f32 = 50.0;
@@ -36,17 +49,13 @@ const ATTRACTION_COST: f64 = 0.0000002;
 const ATTRACTION_RANGE: f32 = 500.0;
 
 impl<'a> InteractionAccumulator for AttractionAccumulator<'a> {
-	fn add_interaction(&mut self, cell: &Cell, other_cell: &Mutex<Cell>, _dt: f64) {
-		let other_cell_lock = other_cell.lock().unwrap();
-		let attraction = self.receptor.strength.dot(other_cell_lock.color);
-		let pos_difference = other_cell_lock.position - cell.position;
-		let distance = pos_difference.length();
-		if distance >= ATTRACTION_RANGE {
-			return;
-		}
+	fn add_interaction(&mut self, cell: &Cell, other_cell: &Cell, _dt: f64) {
+		let color_mass = other_cell.color * other_cell.mass();
+		self.add_attraction(cell, other_cell.position, color_mass);
+	}
 
-		let force_strength = ATTRACTION_STRENGTH * attraction * other_cell_lock.mass();
-		self.force += force_strength * pos_difference.normalize();
+	fn add_cluster(&mut self, cell: &Cell, cluster: &Cluster, _dt: f64) {
+		self.add_attraction(cell, cluster.center_of_mass, cluster.color_mass);
 	}
 
 	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2 {
@@ -59,4 +68,18 @@ impl Receptor for AttractionReceptor {
 	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
 		Box::new(AttractionAccumulator::new(self))
 	}
+
+	fn reach(&self) -> Reach {
+		Reach::Radius(ATTRACTION_RANGE)
+	}
+
+	fn long_range(&self) -> bool {
+		true
+	}
+
+	fn gene(&self) -> Option<ReceptorGene> {
+		Some(ReceptorGene::Attraction {
+			strength: self.strength
+		})
+	}
 }
@@ -1,12 +1,12 @@
-use std::sync::Mutex;
+use std::f32::consts::PI;
 
 use glam::Vec2;
 
-use crate::sim::cell::Cell;
+use crate::sim::{cell::Cell, genome::ReceptorGene, quadtree::Cluster};
 
-use super::{InteractionAccumulator, Receptor};
+use super::{InteractionAccumulator, Reach, Receptor};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct BaseReceptor;
 
 impl BaseReceptor {
@@ -20,7 +20,9 @@ pub struct BaseAccumulator {
 	force: Vec2
 }
 
-const BASE_REPULSION_STRENGTH: f32 = 3000000.0;
+const CONTACT_STIFFNESS: f32 = 10000.0;
+const RESTITUTION: f32 = 0.5;
+const CONTACT_FRICTION: f32 = 0.3;
 const BASE_ENERGY_CONSUMPTION: f64 = 0.1;
 const FRICTION: f32 = 10.0;
 
@@ -28,21 +30,67 @@ impl BaseAccumulator {
 	fn new() -> Self {
 		Self { force: Vec2::ZERO }
 	}
+
+	fn contact_normal(cell: &Cell, other_cell: &Cell) -> Vec2 {
+		let pos_difference = other_cell.position - cell.position;
+		if let Some(normal) = pos_difference.try_normalize() {
+			return normal;
+		}
+		// Coincident cells have no direction between them. Pick one based on their IDs, so that
+		// both cells agree on it and get pushed apart in opposite directions.
+		if cell.id < other_cell.id {
+			Vec2::X
+		} else {
+			Vec2::NEG_X
+		}
+	}
+
+	fn damping_ratio() -> f32 {
+		let log_restitution = RESTITUTION.ln();
+		-log_restitution / (PI.powi(2) + log_restitution.powi(2)).sqrt()
+	}
+
+	fn add_contact(&mut self, cell: &Cell, other_cell: &Cell) {
+		let overlap = cell.size + other_cell.size - cell.position.distance(other_cell.position);
+		if overlap <= 0.0 {
+			return;
+		}
+
+		// Linear spring-dashpot. The spring is scaled by the reduced mass, so every pair of cells
+		// bounces with the same frequency and the coefficient of restitution holds for all sizes.
+		let mass = cell.mass();
+		let other_mass = other_cell.mass();
+		if mass <= 0.0 || other_mass <= 0.0 {
+			return;
+		}
+		let reduced_mass = mass * other_mass / (mass + other_mass);
+		let stiffness = CONTACT_STIFFNESS * reduced_mass;
+		let damping = 2.0 * Self::damping_ratio() * (stiffness * reduced_mass).sqrt();
+
+		let normal = Self::contact_normal(cell, other_cell);
+		let relative_velocity = other_cell.velocity - cell.velocity;
+		let normal_velocity = relative_velocity.dot(normal);
+		let normal_force = (stiffness * overlap - damping * normal_velocity).max(0.0);
+		self.force -= normal_force * normal;
+
+		let tangential_velocity = relative_velocity - normal_velocity * normal;
+		if let Some(tangent) = tangential_velocity.try_normalize() {
+			let friction_force = f32::min(
+				damping * tangential_velocity.length(),
+				CONTACT_FRICTION * normal_force
+			);
+			self.force += friction_force * tangent;
+		}
+	}
 }
 
 impl InteractionAccumulator for BaseAccumulator {
-	fn add_interaction(&mut self, cell: &Cell, other_cell: &Mutex<Cell>, _dt: f64) {
-		let pos_difference = {
-			let other_cell_lock = other_cell.lock().unwrap();
-			other_cell_lock.position - cell.position
-		};
-		let distance = pos_difference.length();
-		let direction = pos_difference.normalize();
-
-		let force_strength = BASE_REPULSION_STRENGTH * (cell.size / distance).powi(2);
-		self.force -= force_strength * direction;
+	fn add_interaction(&mut self, cell: &Cell, other_cell: &Cell, _dt: f64) {
+		self.add_contact(cell, other_cell);
 	}
 
+	fn add_cluster(&mut self, _cell: &Cell, _cluster: &Cluster, _dt: f64) {}
+
 	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2 {
 		cell.consume_energy(BASE_ENERGY_CONSUMPTION * dt);
 		self.force - FRICTION * cell.velocity * cell.mass()
@@ -53,4 +101,72 @@ impl Receptor for BaseReceptor {
 	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
 		Box::new(BaseAccumulator::new())
 	}
+
+	fn reach(&self) -> Reach {
+		Reach::Contact
+	}
+
+	fn gene(&self) -> Option<ReceptorGene> {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn contact_force(cell: &Cell, other_cell: &Cell) -> Vec2 {
+		let mut accumulator = BaseAccumulator::new();
+		accumulator.add_contact(cell, other_cell);
+		accumulator.force
+	}
+
+	fn pair(distance: f32) -> (Cell, Cell) {
+		(
+			Cell::test_cell(0, 5.0, Vec2::ZERO),
+			Cell::test_cell(1, 5.0, Vec2::new(distance, 0.0))
+		)
+	}
+
+	#[test]
+	fn overlapping_cells_are_pushed_apart() {
+		let (cell, other_cell) = pair(8.0);
+		let force = contact_force(&cell, &other_cell);
+		let other_force = contact_force(&other_cell, &cell);
+		assert!(force.x < 0.0 && force.y == 0.0, "{force}");
+		assert!((force + other_force).length() < 1e-3 * force.length());
+
+		// Deeper overlap pushes harder.
+		let (cell, other_cell) = pair(6.0);
+		assert!(contact_force(&cell, &other_cell).x < force.x);
+
+		let (cell, other_cell) = pair(0.0);
+		let force = contact_force(&cell, &other_cell);
+		let other_force = contact_force(&other_cell, &cell);
+		assert!(force.length() > 0.0);
+		assert_eq!(force, -other_force);
+	}
+
+	#[test]
+	fn damping_opposes_approach() {
+		let (cell, mut other_cell) = pair(8.0);
+		let at_rest = contact_force(&cell, &other_cell).x;
+		other_cell.velocity = Vec2::new(-10.0, 0.0);
+		let approaching = contact_force(&cell, &other_cell).x;
+		other_cell.velocity = Vec2::new(10.0, 0.0);
+		let separating = contact_force(&cell, &other_cell).x;
+		assert!(approaching < at_rest && at_rest < separating);
+		// Separating quickly never pulls the cells back together.
+		other_cell.velocity = Vec2::new(1e6, 0.0);
+		assert_eq!(contact_force(&cell, &other_cell), Vec2::ZERO);
+	}
+
+	#[test]
+	fn no_force_beyond_contact() {
+		for distance in [10.0, 10.5, 100.0] {
+			let (cell, mut other_cell) = pair(distance);
+			other_cell.velocity = Vec2::new(-50.0, 20.0);
+			assert_eq!(contact_force(&cell, &other_cell), Vec2::ZERO);
+		}
+	}
 }
@@ -0,0 +1,164 @@
+use glam::{Vec2, Vec3};
+
+use crate::{
+	rand::Rng,
+	sim::{
+		brain::{self, Network},
+		cell::Cell,
+		environment::Environment,
+		genome::ReceptorGene,
+		quadtree::Cluster
+	}
+};
+
+use super::{Effect, InteractionAccumulator, Reach, Receptor};
+
+#[derive(Debug)]
+pub struct BrainReceptor {
+	network: Network
+}
+
+impl BrainReceptor {
+	pub fn new(network: Network) -> Self {
+		Self { network }
+	}
+}
+
+struct BrainAccumulator<'a> {
+	receptor: &'a BrainReceptor,
+	color_directions: [Vec2; 3],
+	crowding: f32,
+	chemical_gradients: Vec<Vec2>,
+	emissions: Vec<f32>
+}
+
+const SENSE_RANGE: f32 = 200.0;
+const SENSING_LENGTH: f32 = 50.0;
+const ENERGY_SCALE: f32 = 10.0;
+const HEALTH_SCALE: f32 = 3.0;
+const SPEED_SCALE: f32 = 100.0;
+const BRAIN_ACCELERATION: f32 = 500.0;
+const MAX_EMISSION_RATE: f32 = 1.0;
+const THINKING_COST: f64 = 0.01;
+const MOVEMENT_COST: f64 = 0.05;
+const EMISSION_COST: f64 = 0.1;
+
+impl<'a> BrainAccumulator<'a> {
+	fn new(receptor: &'a BrainReceptor) -> Self {
+		Self {
+			receptor,
+			color_directions: [Vec2::ZERO; 3],
+			crowding: 0.0,
+			chemical_gradients: vec![Vec2::ZERO; receptor.network.channels()],
+			emissions: Vec::new()
+		}
+	}
+
+	fn inputs(&self, cell: &Cell) -> Vec<f32> {
+		let [red, green, blue] = self
+			.color_directions
+			.map(|direction| direction.clamp_length_max(1.0));
+		let velocity = cell.velocity / SPEED_SCALE;
+		let mut inputs = Vec::with_capacity(brain::num_inputs(self.chemical_gradients.len()));
+		inputs.extend([
+			red.x,
+			red.y,
+			green.x,
+			green.y,
+			blue.x,
+			blue.y,
+			self.crowding.tanh(),
+			(cell.energy as f32 / ENERGY_SCALE).tanh(),
+			cell.health / HEALTH_SCALE
+		]);
+		for gradient in &self.chemical_gradients {
+			inputs.extend([gradient.x, gradient.y]);
+		}
+		inputs.extend([velocity.x.tanh(), velocity.y.tanh()]);
+		inputs
+	}
+
+	fn add_neighbour(&mut self, cell: &Cell, position: Vec2, color: Vec3) {
+		let pos_difference = position - cell.position;
+		let distance = pos_difference.length();
+		if distance >= SENSE_RANGE {
+			return;
+		}
+		let Some(direction) = pos_difference.try_normalize() else {
+			return;
+		};
+		let proximity = 1.0 - distance / SENSE_RANGE;
+		for (color_direction, channel) in self.color_directions.iter_mut().zip(color.to_array()) {
+			*color_direction += channel * proximity * direction;
+		}
+		self.crowding += proximity;
+	}
+}
+
+impl<'a> InteractionAccumulator for BrainAccumulator<'a> {
+	fn add_interaction(&mut self, cell: &Cell, other_cell: &Cell, _dt: f64) {
+		self.add_neighbour(cell, other_cell.position, other_cell.color);
+	}
+
+	fn add_cluster(&mut self, _cell: &Cell, _cluster: &Cluster, _dt: f64) {}
+
+	fn sense(&mut self, cell: &Cell, environment: &Environment, _rng: &mut Rng) {
+		for (channel, gradient) in self.chemical_gradients.iter_mut().enumerate() {
+			let relative_gradient = environment
+				.chemicals
+				.relative_gradient(channel, cell.position);
+			*gradient = (relative_gradient * SENSING_LENGTH).clamp_length_max(1.0);
+		}
+	}
+
+	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2 {
+		let outputs = self.receptor.network.evaluate(&self.inputs(cell));
+		let (movement, emission_outputs) = outputs.split_at(2);
+		let movement = Vec2::new(movement[0], movement[1]);
+		let emission_rates: Vec<f32> = emission_outputs
+			.iter()
+			.map(|output| output.max(0.0))
+			.collect();
+
+		let emission_cost: f64 = emission_rates
+			.iter()
+			.map(|rate| (rate * MAX_EMISSION_RATE) as f64 * EMISSION_COST)
+			.sum();
+		let energy_cost =
+			(THINKING_COST + MOVEMENT_COST * movement.length() as f64 + emission_cost) * dt;
+		let efficiency = cell.consume_energy(energy_cost);
+
+		self.emissions = emission_rates
+			.iter()
+			.map(|rate| rate * MAX_EMISSION_RATE * efficiency * dt as f32)
+			.collect();
+		BRAIN_ACCELERATION * efficiency * cell.mass() * movement
+	}
+
+	fn effects(&mut self, cell: &Cell, effects: &mut Vec<Effect>) {
+		for (channel, &amount) in self.emissions.iter().enumerate() {
+			if amount <= 0.0 {
+				continue;
+			}
+			effects.push(Effect::Emission {
+				channel,
+				position: cell.position,
+				amount
+			});
+		}
+	}
+}
+
+impl Receptor for BrainReceptor {
+	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
+		Box::new(BrainAccumulator::new(self))
+	}
+
+	fn reach(&self) -> Reach {
+		Reach::Radius(SENSE_RANGE)
+	}
+
+	fn gene(&self) -> Option<ReceptorGene> {
+		Some(ReceptorGene::Brain(self.network.clone()))
+	}
+}
@@ -0,0 +1,68 @@
+use glam::Vec2;
+
+use crate::{
+	rand::Rng,
+	sim::{cell::Cell, environment::Environment, genome::ReceptorGene, quadtree::Cluster}
+};
+
+use super::{InteractionAccumulator, Reach, Receptor};
+
+#[derive(Debug)]
+pub struct ChemotaxisReceptor {
+	channel: usize,
+	strength: f32
+}
+
+impl ChemotaxisReceptor {
+	pub fn new(channel: usize, strength: f32) -> Self {
+		Self { channel, strength }
+	}
+}
+
+struct ChemotaxisAccumulator<'a> {
+	receptor: &'a ChemotaxisReceptor,
+	response: Vec2
+}
+
+const CHEMOTAXIS_COST: f64 = 0.05;
+const CHEMOTAXIS_ACCELERATION: f32 = 500.0;
+const SENSING_LENGTH: f32 = 50.0;
+
+impl<'a> InteractionAccumulator for ChemotaxisAccumulator<'a> {
+	fn add_interaction(&mut self, _cell: &Cell, _other_cell: &Cell, _dt: f64) {}
+
+	fn add_cluster(&mut self, _cell: &Cell, _cluster: &Cluster, _dt: f64) {}
+
+	fn sense(&mut self, cell: &Cell, environment: &Environment, _rng: &mut Rng) {
+		let gradient = environment
+			.chemicals
+			.relative_gradient(self.receptor.channel, cell.position);
+		self.response = (gradient * SENSING_LENGTH).clamp_length_max(1.0);
+	}
+
+	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2 {
+		let energy_cost = self.receptor.strength.abs() as f64 * CHEMOTAXIS_COST * dt;
+		let efficiency = cell.consume_energy(energy_cost);
+		CHEMOTAXIS_ACCELERATION * self.receptor.strength * efficiency * cell.mass() * self.response
+	}
+}
+
+impl Receptor for ChemotaxisReceptor {
+	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
+		Box::new(ChemotaxisAccumulator {
+			receptor: self,
+			response: Vec2::ZERO
+		})
+	}
+
+	fn reach(&self) -> Reach {
+		Reach::None
+	}
+
+	fn gene(&self) -> Option<ReceptorGene> {
+		Some(ReceptorGene::Chemotaxis {
+			channel: self.channel,
+			strength: self.strength
+		})
+	}
+}
@@ -0,0 +1,68 @@
+use glam::Vec2;
+
+use crate::sim::{cell::Cell, genome::ReceptorGene, quadtree::Cluster};
+
+use super::{Effect, InteractionAccumulator, Reach, Receptor};
+
+#[derive(Debug)]
+pub struct EmissionReceptor {
+	channel: usize,
+	rate: f32
+}
+
+impl EmissionReceptor {
+	pub fn new(channel: usize, rate: f32) -> Self {
+		Self { channel, rate }
+	}
+}
+
+struct EmissionAccumulator<'a> {
+	receptor: &'a EmissionReceptor,
+	amount: f32
+}
+
+const EMISSION_COST: f64 = 0.1;
+
+impl<'a> InteractionAccumulator for EmissionAccumulator<'a> {
+	fn add_interaction(&mut self, _cell: &Cell, _other_cell: &Cell, _dt: f64) {}
+
+	fn add_cluster(&mut self, _cell: &Cell, _cluster: &Cluster, _dt: f64) {}
+
+	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2 {
+		let energy_cost = self.receptor.rate as f64 * EMISSION_COST * dt;
+		let efficiency = cell.consume_energy(energy_cost);
+		self.amount = self.receptor.rate * efficiency * dt as f32;
+		Vec2::ZERO
+	}
+
+	fn effects(&mut self, cell: &Cell, effects: &mut Vec<Effect>) {
+		if self.amount <= 0.0 {
+			return;
+		}
+		effects.push(Effect::Emission {
+			channel: self.receptor.channel,
+			position: cell.position,
+			amount: self.amount
+		});
+	}
+}
+
+impl Receptor for EmissionReceptor {
+	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
+		Box::new(EmissionAccumulator {
+			receptor: self,
+			amount: 0.0
+		})
+	}
+
+	fn reach(&self) -> Reach {
+		Reach::None
+	}
+
+	fn gene(&self) -> Option<ReceptorGene> {
+		Some(ReceptorGene::Emission {
+			channel: self.channel,
+			rate: self.rate
+		})
+	}
+}
@@ -1,18 +1,72 @@
-use std::{fmt::Debug, sync::Mutex};
+use std::fmt::Debug;
 
 use glam::Vec2;
+use uuid::Uuid;
 
-use super::cell::Cell;
+use crate::rand::Rng;
 
+use super::{cell::Cell, environment::Environment, genome::ReceptorGene, quadtree::Cluster};
+
+pub mod adhesion;
 pub mod attract;
 pub mod base;
+pub mod brain;
+pub mod chemotaxis;
+pub mod emission;
+pub mod predation;
+pub mod tumble;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reach {
+	None,
+	Unlimited,
+	Radius(f32),
+	Contact
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Effect {
+	Predation {
+		predator: Uuid,
+		prey: Uuid,
+		strength: f32
+	},
+	Emission {
+		channel: usize,
+		position: Vec2,
+		amount: f32
+	},
+	Adhesion {
+		cell: Uuid,
+		other: Uuid,
+		strength: f32
+	}
+}
 
 pub trait InteractionAccumulator {
-	fn add_interaction(&mut self, cell: &Cell, other_cell: &Mutex<Cell>, dt: f64);
+	fn add_interaction(&mut self, cell: &Cell, other_cell: &Cell, dt: f64);
+
+	/// Adds a group of cells approximated as a whole. Only receptors reporting `long_range` are
+	/// passed clusters.
+	fn add_cluster(&mut self, cell: &Cell, cluster: &Cluster, dt: f64);
+
+	fn sense(&mut self, _cell: &Cell, _environment: &Environment, _rng: &mut Rng) {}
 
 	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2;
+
+	fn effects(&mut self, _cell: &Cell, _effects: &mut Vec<Effect>) {}
 }
 
 pub trait Receptor: Debug + Send + Sync {
 	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a>;
+
+	fn reach(&self) -> Reach {
+		Reach::Unlimited
+	}
+
+	fn long_range(&self) -> bool {
+		false
+	}
+
+	fn gene(&self) -> Option<ReceptorGene>;
 }
@@ -0,0 +1,167 @@
+use glam::Vec2;
+use uuid::Uuid;
+
+use crate::sim::{cell::Cell, genome::ReceptorGene, quadtree::Cluster};
+
+use super::{Effect, InteractionAccumulator, Reach, Receptor};
+
+#[derive(Debug)]
+pub struct PredationReceptor {
+	strength: f32
+}
+
+impl PredationReceptor {
+	pub fn new(strength: f32) -> Self {
+		Self { strength }
+	}
+}
+
+struct PredationAccumulator<'a> {
+	receptor: &'a PredationReceptor,
+	prey: Vec<Uuid>,
+	efficiency: f32
+}
+
+impl<'a> PredationAccumulator<'a> {
+	fn new(receptor: &'a PredationReceptor) -> Self {
+		Self {
+			receptor,
+			prey: Vec::new(),
+			efficiency: 0.0
+		}
+	}
+
+	/// Cells prey on touching cells that are weaker overall, counting both size and health. As
+	/// this compares a single number, two cells can never prey on each other.
+	fn is_prey(cell: &Cell, other_cell: &Cell) -> bool {
+		let touching = cell.position.distance(other_cell.position) < cell.size + other_cell.size;
+		touching && vigour(other_cell) < vigour(cell)
+	}
+}
+
+fn vigour(cell: &Cell) -> f32 {
+	cell.size * cell.health.max(0.0)
+}
+
+const PREDATION_COST: f64 = 0.05;
+const HEALTH_DRAIN: f32 = 1.0;
+const ENERGY_DRAIN: f64 = 0.5;
+const MASS_DRAIN: f32 = 0.2;
+const MASS_EFFICIENCY: f32 = 0.5;
+/// Most of the prey's mass that can be taken in one tick, however strong the predator.
+const MAX_MASS_DRAIN: f32 = 0.5;
+/// Prey drained below this size is eaten whole.
+const MIN_PREY_SIZE: f32 = 1.0;
+
+impl<'a> InteractionAccumulator for PredationAccumulator<'a> {
+	fn add_interaction(&mut self, cell: &Cell, other_cell: &Cell, _dt: f64) {
+		if Self::is_prey(cell, other_cell) {
+			self.prey.push(other_cell.id);
+		}
+	}
+
+	fn add_cluster(&mut self, _cell: &Cell, _cluster: &Cluster, _dt: f64) {}
+
+	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2 {
+		if !self.prey.is_empty() {
+			let energy_cost = self.receptor.strength as f64 * PREDATION_COST * dt;
+			self.efficiency = cell.consume_energy(energy_cost);
+		}
+		Vec2::ZERO
+	}
+
+	fn effects(&mut self, cell: &Cell, effects: &mut Vec<Effect>) {
+		let strength = self.receptor.strength * self.efficiency;
+		if strength <= 0.0 {
+			return;
+		}
+		effects.extend(self.prey.drain(..).map(|prey| Effect::Predation {
+			predator: cell.id,
+			prey,
+			strength
+		}));
+	}
+}
+
+impl Receptor for PredationReceptor {
+	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
+		Box::new(PredationAccumulator::new(self))
+	}
+
+	fn reach(&self) -> Reach {
+		Reach::Contact
+	}
+
+	fn gene(&self) -> Option<ReceptorGene> {
+		Some(ReceptorGene::Predation {
+			strength: self.strength
+		})
+	}
+}
+
+pub fn apply_predation(predator: &mut Cell, prey: &mut Cell, strength: f32, dt: f64) {
+	prey.health -= HEALTH_DRAIN * strength * dt as f32;
+
+	let energy = prey.energy * (ENERGY_DRAIN * strength as f64 * dt).min(1.0);
+	prey.energy -= energy;
+	predator.energy += energy;
+
+	let mut mass = prey.mass() * (MASS_DRAIN * strength * dt as f32).min(MAX_MASS_DRAIN);
+	prey.set_mass(prey.mass() - mass);
+	if prey.size < MIN_PREY_SIZE {
+		// Killing the prey removes it before the next tick, when a cell without mass would be
+		// accelerated by dividing by zero.
+		mass += prey.mass();
+		prey.set_mass(0.0);
+		prey.health = 0.0;
+	}
+	predator.set_mass(predator.mass() + MASS_EFFICIENCY * mass);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn predation_is_one_sided() {
+		let cell = |id, size, health| {
+			let mut cell = Cell::test_cell(id, size, Vec2::new(id as f32, 0.0));
+			cell.health = health;
+			cell
+		};
+		// Larger but weaker, just as strong, smaller and weaker, and larger and healthier.
+		for (size, health) in [(8.0, 1.0), (5.0, 2.0), (4.0, 1.5), (6.0, 3.0)] {
+			let predator = cell(0, 5.0, 2.0);
+			let other = cell(1, size, health);
+			let eats = PredationAccumulator::is_prey(&predator, &other);
+			let eaten = PredationAccumulator::is_prey(&other, &predator);
+			assert!(!(eats && eaten), "{size} and {health} prey on each other");
+		}
+		assert!(PredationAccumulator::is_prey(
+			&cell(0, 5.0, 2.0),
+			&cell(1, 4.0, 1.5)
+		));
+	}
+
+	#[test]
+	fn strong_predation_kills_instead_of_leaving_massless_prey() {
+		let mut predator = Cell::test_cell(0, 5.0, Vec2::ZERO);
+		let mut prey = Cell::test_cell(1, 4.0, Vec2::X);
+		prey.health = 1e9;
+		let predator_mass = predator.mass();
+		let prey_mass = prey.mass();
+		for _ in 0..10 {
+			apply_predation(&mut predator, &mut prey, 1000.0, 1.0);
+			assert!(prey.size >= MIN_PREY_SIZE || prey.health <= 0.0);
+			if prey.health <= 0.0 {
+				break;
+			}
+		}
+		assert!(prey.health <= 0.0);
+		assert_eq!(prey.mass(), 0.0);
+		assert!(predator.mass().is_finite());
+		assert!(predator.mass() > predator_mass);
+		let eaten = (predator.mass() - predator_mass) / MASS_EFFICIENCY;
+		assert!((eaten - prey_mass).abs() < 1e-3);
+	}
+}
@@ -0,0 +1,93 @@
+use glam::Vec2;
+
+use crate::{
+	rand::Rng,
+	sim::{cell::Cell, environment::Environment, genome::ReceptorGene, quadtree::Cluster}
+};
+
+use super::{InteractionAccumulator, Reach, Receptor};
+
+#[derive(Debug)]
+pub struct RunAndTumbleReceptor {
+	channel: usize,
+	speed: f32
+}
+
+impl RunAndTumbleReceptor {
+	pub fn new(channel: usize, speed: f32) -> Self {
+		Self { channel, speed }
+	}
+}
+
+struct RunAndTumbleAccumulator<'a> {
+	receptor: &'a RunAndTumbleReceptor,
+	heading: Option<Vec2>,
+	tumble_rate: f32,
+	roll: f32,
+	tumble_heading: Vec2
+}
+
+const RUN_COST: f64 = 0.05;
+const RUN_ACCELERATION: f32 = 500.0;
+const BASE_TUMBLE_RATE: f32 = 1.0;
+const MAX_TUMBLE_RATE: f32 = 10.0;
+const TUMBLE_SENSITIVITY: f32 = 5.0;
+const SENSING_LENGTH: f32 = 50.0;
+
+impl<'a> InteractionAccumulator for RunAndTumbleAccumulator<'a> {
+	fn add_interaction(&mut self, _cell: &Cell, _other_cell: &Cell, _dt: f64) {}
+
+	fn add_cluster(&mut self, _cell: &Cell, _cluster: &Cluster, _dt: f64) {}
+
+	fn sense(&mut self, cell: &Cell, environment: &Environment, rng: &mut Rng) {
+		// The cell keeps running in the direction it is moving, and tumbles into a new random
+		// direction less often while the concentration ahead of it is rising.
+		self.heading = cell.velocity.try_normalize();
+		let change = self.heading.map_or(0.0, |heading| {
+			let gradient = environment
+				.chemicals
+				.relative_gradient(self.receptor.channel, cell.position);
+			gradient.dot(heading) * SENSING_LENGTH
+		});
+		self.tumble_rate =
+			(BASE_TUMBLE_RATE * (-TUMBLE_SENSITIVITY * change).exp()).min(MAX_TUMBLE_RATE);
+		self.roll = rng.rand();
+		self.tumble_heading = rng.rand_direction();
+	}
+
+	fn complete(&mut self, cell: &mut Cell, dt: f64) -> Vec2 {
+		let heading = match self.heading {
+			Some(heading) if self.roll >= self.tumble_rate * dt as f32 => heading,
+			_ => {
+				cell.velocity = self.tumble_heading * cell.velocity.length();
+				self.tumble_heading
+			}
+		};
+		let energy_cost = self.receptor.speed as f64 * RUN_COST * dt;
+		let efficiency = cell.consume_energy(energy_cost);
+		RUN_ACCELERATION * self.receptor.speed * efficiency * cell.mass() * heading
+	}
+}
+
+impl Receptor for RunAndTumbleReceptor {
+	fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
+		Box::new(RunAndTumbleAccumulator {
+			receptor: self,
+			heading: None,
+			tumble_rate: 0.0,
+			roll: 0.0,
+			tumble_heading: Vec2::ZERO
+		})
+	}
+
+	fn reach(&self) -> Reach {
+		Reach::None
+	}
+
+	fn gene(&self) -> Option<ReceptorGene> {
+		Some(ReceptorGene::RunAndTumble {
+			channel: self.channel,
+			speed: self.speed
+		})
+	}
+}
@@ -0,0 +1,334 @@
+use std::{fmt, fs, io, path::Path};
+
+use glam::{Vec2, Vec3};
+use serde::Deserialize;
+
+use crate::rand::Rng;
+
+use super::{
+	bonds::BondConfig,
+	bounds::WorldBounds,
+	chemicals::ChemicalConfig,
+	genome::{Genome, MutationConfig, ReceptorGene},
+	nutrients::NutrientConfig,
+	obstacles::Obstacle,
+	LongRangeMode, Simulation
+};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+	pub seed: Option<u64>,
+	pub world: WorldSettings,
+	pub cells: Vec<CellSpec>,
+	pub populations: Vec<PopulationSpec>
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldSettings {
+	pub bounds: WorldBounds,
+	pub opening_angle: Option<f32>,
+	pub obstacles: Vec<Obstacle>,
+	pub mutation: MutationConfig,
+	pub nutrients: NutrientConfig,
+	pub chemicals: ChemicalConfig,
+	pub bonds: BondConfig
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CellSpec {
+	pub position: Vec2,
+	pub size: f32,
+	pub color: Vec3,
+	#[serde(default)]
+	pub receptors: Vec<ReceptorGene>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PopulationSpec {
+	pub count: usize,
+	pub region: Region,
+	pub size: Distribution<f32>,
+	pub color: Distribution<Vec3>,
+	#[serde(default)]
+	pub receptors: Vec<ReceptorGene>,
+	/// Number of times each cell's genome is mutated before it is placed, to seed the population
+	/// with some variation.
+	#[serde(default)]
+	pub mutations: u32
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Region {
+	Circle { center: Vec2, radius: f32 },
+	Rectangle { center: Vec2, size: Vec2 }
+}
+
+impl Region {
+	fn sample(&self, rng: &mut Rng) -> Vec2 {
+		match *self {
+			Self::Circle { center, radius } => {
+				center + radius * rng.rand().sqrt() * rng.rand_direction()
+			}
+			Self::Rectangle { center, size } => {
+				let offset = Vec2::new(rng.rand() - 0.5, rng.rand() - 0.5);
+				center + offset * size
+			}
+		}
+	}
+}
+
+/// Either a fixed value or a range values are picked from uniformly. Vectors are sampled
+/// component-wise.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+pub enum Distribution<T> {
+	Fixed(T),
+	Uniform { min: T, max: T }
+}
+
+impl Distribution<f32> {
+	fn sample(&self, rng: &mut Rng) -> f32 {
+		match *self {
+			Self::Fixed(value) => value,
+			Self::Uniform { min, max } => rng.rand_in_range(min, max)
+		}
+	}
+
+	fn is_positive(&self) -> bool {
+		match *self {
+			Self::Fixed(value) => is_positive(value),
+			Self::Uniform { min, max } => is_positive(min) && is_positive(max)
+		}
+	}
+}
+
+fn is_positive(value: f32) -> bool {
+	value.is_finite() && value > 0.0
+}
+
+impl Distribution<Vec3> {
+	fn sample(&self, rng: &mut Rng) -> Vec3 {
+		match *self {
+			Self::Fixed(value) => value,
+			Self::Uniform { min, max } => Vec3::new(
+				rng.rand_in_range(min.x, max.x),
+				rng.rand_in_range(min.y, max.y),
+				rng.rand_in_range(min.z, max.z)
+			)
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+	Io(io::Error),
+	Parse(toml::de::Error),
+	Invalid(String)
+}
+
+impl fmt::Display for ScenarioError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Io(err) => write!(f, "{err}"),
+			Self::Parse(err) => write!(f, "Invalid scenario: {err}"),
+			Self::Invalid(message) => write!(f, "Invalid scenario: {message}")
+		}
+	}
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<io::Error> for ScenarioError {
+	fn from(value: io::Error) -> Self {
+		Self::Io(value)
+	}
+}
+
+impl From<toml::de::Error> for ScenarioError {
+	fn from(value: toml::de::Error) -> Self {
+		Self::Parse(value)
+	}
+}
+
+impl Scenario {
+	pub fn parse(source: &str) -> Result<Self, ScenarioError> {
+		let scenario: Self = toml::from_str(source)?;
+		scenario.validate()?;
+		Ok(scenario)
+	}
+
+	/// Rejects values that would make the simulation panic or fill up with NaN: cells without
+	/// mass and fields without tiles. World bounds are already checked while parsing.
+	fn validate(&self) -> Result<(), ScenarioError> {
+		let check = |valid: bool, message: &str| {
+			valid
+				.then_some(())
+				.ok_or_else(|| ScenarioError::Invalid(message.to_owned()))
+		};
+		let world = &self.world;
+		for (name, field) in [
+			("nutrient", world.nutrients.field),
+			("chemical", world.chemicals.field)
+		] {
+			check(
+				is_positive(field.tile_size),
+				&format!("{name} tile size must be positive")
+			)?;
+			check(
+				is_positive(field.size.x) && is_positive(field.size.y),
+				&format!("{name} field size must be positive")
+			)?;
+		}
+		if let Some(opening_angle) = world.opening_angle {
+			check(
+				opening_angle.is_finite() && opening_angle >= 0.0,
+				"opening angle must not be negative"
+			)?;
+		}
+		// A jitter of 2 or more can shrink a mutated cell to nothing.
+		let size_jitter = world.mutation.size_jitter;
+		check(
+			(0.0..2.0).contains(&size_jitter),
+			"size jitter must be at least 0 and less than 2"
+		)?;
+		for cell in &self.cells {
+			check(is_positive(cell.size), "cell sizes must be positive")?;
+		}
+		for population in &self.populations {
+			check(
+				population.size.is_positive(),
+				"population sizes must be positive"
+			)?;
+		}
+		Ok(())
+	}
+
+	pub fn load(path: &Path) -> Result<Self, ScenarioError> {
+		Self::parse(&fs::read_to_string(path)?)
+	}
+
+	pub fn build(&self, seed: u64) -> Simulation {
+		let mut rng = Rng::new(seed);
+		let mut sim = Simulation::new(rng.rand_u64());
+		let world = &self.world;
+		let mutation = MutationConfig {
+			chemical_channels: world.chemicals.channels,
+			..world.mutation
+		};
+		sim.set_mutation_config(mutation);
+		sim.set_nutrient_config(world.nutrients);
+		sim.set_chemical_config(world.chemicals);
+		sim.set_bond_config(world.bonds);
+		if let Some(opening_angle) = world.opening_angle {
+			sim.set_long_range_mode(LongRangeMode::BarnesHut { opening_angle });
+		}
+		sim.set_bounds(world.bounds);
+		for obstacle in &world.obstacles {
+			sim.add_obstacle(obstacle.clone());
+		}
+
+		for cell in &self.cells {
+			let genome = Genome::new(cell.color, cell.size, cell.receptors.clone());
+			sim.add_cell(&genome, cell.position);
+		}
+		for population in &self.populations {
+			for _ in 0..population.count {
+				let mut genome = Genome::new(
+					population.color.sample(&mut rng),
+					population.size.sample(&mut rng),
+					population.receptors.clone()
+				);
+				for _ in 0..population.mutations {
+					genome.mutate(&mut rng, &mutation);
+				}
+				sim.add_cell(&genome, population.region.sample(&mut rng));
+			}
+		}
+		sim
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const BUNDLED: [(&str, &str); 2] = [
+		("default", include_str!("../../scenarios/default.toml")),
+		("soup", include_str!("../../scenarios/soup.toml"))
+	];
+
+	#[test]
+	fn bundled_scenarios_parse_and_build() {
+		for (name, source) in BUNDLED {
+			let scenario =
+				Scenario::parse(source).unwrap_or_else(|err| panic!("{name} scenario: {err}"));
+			let simulation = scenario.build(1);
+			let expected_cells = scenario.cells.len()
+				+ scenario
+					.populations
+					.iter()
+					.map(|population| population.count)
+					.sum::<usize>();
+			assert_eq!(
+				simulation.cells().count(),
+				expected_cells,
+				"{name} scenario"
+			);
+			assert_eq!(
+				*simulation.bounds(),
+				scenario.world.bounds,
+				"{name} scenario"
+			);
+			assert_eq!(
+				simulation.obstacles().len(),
+				scenario.world.obstacles.len(),
+				"{name} scenario"
+			);
+		}
+	}
+
+	#[test]
+	fn building_is_deterministic() {
+		let scenario = Scenario::parse(BUNDLED[1].1).unwrap();
+		let cells = |seed| {
+			let simulation = scenario.build(seed);
+			let cells: Vec<(Vec2, f32)> = simulation
+				.cells()
+				.map(|cell| (cell.position, cell.size))
+				.collect();
+			cells
+		};
+		assert_eq!(cells(4), cells(4));
+		assert_ne!(cells(4), cells(5));
+	}
+
+	#[test]
+	fn invalid_values_are_rejected() {
+		for source in [
+			"[world.nutrients.field]\ntile_size = 0.0",
+			"[world.chemicals.field]\nsize = [4000.0, -1.0]",
+			"[world]\nbounds = { torus = { size = [0.0, 0.0] } }",
+			"[world]\nbounds = { rectangle = { size = [-100.0, 100.0] } }",
+			"[world]\nopening_angle = -0.5",
+			"[world.mutation]\nsize_jitter = 2.0",
+			"[[cells]]\nposition = [0.0, 0.0]\nsize = 0.0\ncolor = [1.0, 1.0, 1.0]",
+			"[[populations]]\ncount = 1\nregion = { circle = { center = [0.0, 0.0], radius = 1.0 \
+			 } }\nsize = { min = -1.0, max = 5.0 }\ncolor = [1.0, 1.0, 1.0]"
+		] {
+			assert!(Scenario::parse(source).is_err(), "{source:?} was accepted");
+		}
+	}
+
+	#[test]
+	fn unknown_settings_are_rejected() {
+		assert!(matches!(
+			Scenario::parse("[world]\nbound = \"open\""),
+			Err(ScenarioError::Parse(_))
+		));
+	}
+}
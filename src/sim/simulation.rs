@@ -1,158 +1,680 @@
-use std::{
-	collections::HashMap,
-	sync::{Arc, Mutex, MutexGuard}
-};
+use std::{collections::BTreeMap, f32::consts::TAU, io::Write, sync::mpsc::Receiver};
 
-use glam::{Vec2, Vec3};
+use glam::Vec2;
+use rayon::{
+	prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator},
+	ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder
+};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
 	ecs::{Ecs, Entity},
-	particles::{GroupSpawnProps, ParticleSystem},
-	render::{layers, ObjectProvider}
+	rand::Rng
 };
 
 use super::{
+	bonds::{Bond, BondConfig, Bonds},
+	bounds::WorldBounds,
 	cell::Cell,
-	receptors::{base::BaseReceptor, Receptor}
+	chemicals::{ChemicalConfig, ChemicalFields},
+	environment::Environment,
+	events::{Event, EventBus, EventKind},
+	genome::{Genome, MutationConfig, ReceptorGene},
+	lineage::Lineage,
+	nutrients::{NutrientConfig, NutrientField},
+	obstacles::Obstacle,
+	quadtree::{QuadTree, TreeEntry},
+	receptors::{base::BaseReceptor, predation, Effect, Receptor},
+	snapshot::{CellState, SimulationState},
+	spatial::{Neighbourhood, SpatialHash},
+	stats::{StatsCollector, StatsConfig, StatsWriter}
 };
 
 pub trait Tick {
 	fn tick(&mut self, dt: f64);
 }
 
-#[derive(Debug)]
-pub struct Simulation {
-	particle_system: Arc<Mutex<ParticleSystem>>,
-	ecs: Mutex<Ecs<Box<dyn Receptor>>>,
-	cells: HashMap<Uuid, Mutex<Cell>>
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LongRangeMode {
+	Exact,
+	BarnesHut { opening_angle: f32 }
 }
 
-struct DeathParticles {
-	color: Vec3,
-	position: Vec2,
-	spread: f32
+#[derive(Debug)]
+pub struct Simulation {
+	rng: Rng,
+	particle_seed: u64,
+	long_range_mode: LongRangeMode,
+	bounds: WorldBounds,
+	obstacles: Vec<Obstacle>,
+	thread_pool: Option<ThreadPool>,
+	mutation: MutationConfig,
+	nutrients: NutrientField,
+	chemicals: ChemicalFields,
+	bonds: Bonds,
+	ecs: Ecs<Box<dyn Receptor>>,
+	cells: BTreeMap<Uuid, Cell>,
+	snapshot: Vec<Cell>,
+	lineage: Lineage,
+	stats: StatsCollector,
+	tick_count: u64,
+	events: EventBus
 }
 
-impl DeathParticles {
-	fn for_cell(cell: &MutexGuard<'_, Cell>) -> Self {
+impl Simulation {
+	pub fn new(seed: u64) -> Self {
+		let mut rng = Rng::new(seed);
+		let particle_seed = rng.rand_u64();
 		Self {
-			color: cell.color,
-			position: cell.position,
-			spread: cell.size
+			rng,
+			particle_seed,
+			long_range_mode: LongRangeMode::Exact,
+			bounds: WorldBounds::Open,
+			obstacles: Vec::new(),
+			thread_pool: None,
+			mutation: MutationConfig::default(),
+			nutrients: NutrientField::new(NutrientConfig::default(), &WorldBounds::Open),
+			chemicals: ChemicalFields::new(ChemicalConfig::default(), &WorldBounds::Open),
+			bonds: Bonds::new(BondConfig::default()),
+			ecs: Ecs::new(),
+			cells: BTreeMap::new(),
+			snapshot: Vec::new(),
+			lineage: Lineage::new(),
+			stats: StatsCollector::new(StatsConfig::default()),
+			tick_count: 0,
+			events: EventBus::new()
 		}
 	}
-}
 
-impl Simulation {
-	pub fn new(particle_system: Arc<Mutex<ParticleSystem>>) -> Self {
-		Self {
-			particle_system,
-			ecs: Mutex::new(Ecs::new()),
-			cells: HashMap::new()
+	/// Restores a simulation from a captured state. Worker threads, statistics writers and event
+	/// subscriptions aren't part of the state and have to be set up again.
+	pub fn from_state(state: SimulationState) -> Self {
+		let mut bonds = Bonds::new(state.bond_config);
+		for (a, b, bond) in state.bonds {
+			bonds.form(a, b, bond);
+		}
+		let mut stats = StatsCollector::new(state.stats_config);
+		stats.restore_samples(state.stats_samples);
+
+		let mut sim = Self {
+			rng: state.rng,
+			particle_seed: state.particle_seed,
+			long_range_mode: state.long_range_mode,
+			bounds: state.bounds,
+			obstacles: state.obstacles,
+			mutation: state.mutation,
+			nutrients: state.nutrients,
+			chemicals: state.chemicals,
+			bonds,
+			lineage: state.lineage,
+			stats,
+			tick_count: state.tick_count,
+			..Self::new(0)
+		};
+		for CellState {
+			mut cell,
+			receptors
+		} in state.cells
+		{
+			let receptors = receptors.iter().map(ReceptorGene::build).collect();
+			cell.entity = sim.create_cell_entity(receptors);
+			sim.cells.insert(cell.id, cell);
 		}
+		sim
 	}
 
-	pub fn add_cell(
+	pub fn state(&self) -> SimulationState {
+		SimulationState {
+			rng: self.rng.clone(),
+			particle_seed: self.particle_seed,
+			tick_count: self.tick_count,
+			long_range_mode: self.long_range_mode,
+			bounds: self.bounds,
+			obstacles: self.obstacles.clone(),
+			mutation: self.mutation,
+			nutrients: self.nutrients.clone(),
+			chemicals: self.chemicals.clone(),
+			bond_config: self.bonds.config(),
+			bonds: self
+				.bonds
+				.iter()
+				.map(|(a, b, bond)| (a, b, *bond))
+				.collect(),
+			cells: self
+				.cells
+				.values()
+				.map(|cell| CellState {
+					cell: cell.clone(),
+					receptors: self.genome(cell).receptors
+				})
+				.collect(),
+			lineage: self.lineage.clone(),
+			stats_config: self.stats.config(),
+			stats_samples: self.stats.samples().cloned().collect()
+		}
+	}
+
+	pub fn subscribe<F: Fn(&EventKind) -> bool + Send + 'static>(
 		&mut self,
-		size: f32,
-		color: Vec3,
-		position: Vec2,
-		receptors: Vec<Box<dyn Receptor>>
+		filter: F
+	) -> Receiver<Event> {
+		self.events.subscribe(filter)
+	}
+
+	pub fn add_event_log<F: Fn(&EventKind) -> bool + Send + 'static>(
+		&mut self,
+		output: Box<dyn Write + Send>,
+		filter: F
 	) {
-		let entity = self.create_cell_entity(receptors);
-		let mut cell = Cell::new(entity);
-		cell.size = size;
-		cell.color = color;
+		self.events.add_log(output, filter);
+	}
+
+	/// Seed for randomness that is only for show, such as particles. It follows from the
+	/// simulation's seed, but drawing from it doesn't change how the simulation plays out.
+	pub fn particle_seed(&self) -> u64 {
+		self.particle_seed
+	}
+
+	pub fn tick_count(&self) -> u64 {
+		self.tick_count
+	}
+
+	pub fn cells(&self) -> impl Iterator<Item = &Cell> {
+		self.cells.values()
+	}
+
+	pub fn cell(&self, id: Uuid) -> Option<&Cell> {
+		self.cells.get(&id)
+	}
+
+	pub fn bonds(&self) -> &Bonds {
+		&self.bonds
+	}
+
+	pub fn bounds(&self) -> &WorldBounds {
+		&self.bounds
+	}
+
+	pub fn lineage(&self) -> &Lineage {
+		&self.lineage
+	}
+
+	pub fn stats(&self) -> &StatsCollector {
+		&self.stats
+	}
+
+	pub fn set_stats_config(&mut self, config: StatsConfig) {
+		self.stats.set_config(config);
+	}
+
+	pub fn add_stats_writer(&mut self, writer: StatsWriter) {
+		self.stats.add_writer(writer);
+	}
+
+	pub fn set_long_range_mode(&mut self, mode: LongRangeMode) {
+		self.long_range_mode = mode;
+	}
+
+	/// Changes the shape of the world. The nutrient and chemical fields are rebuilt to cover it,
+	/// which resets their contents.
+	pub fn set_bounds(&mut self, bounds: WorldBounds) {
+		self.bounds = bounds;
+		self.nutrients = NutrientField::new(self.nutrients.config(), &bounds);
+		self.chemicals = ChemicalFields::new(self.chemicals.config(), &bounds);
+		for cell in self.cells.values_mut() {
+			Self::confine(&self.bounds, &self.obstacles, cell);
+		}
+	}
+
+	pub fn add_obstacle(&mut self, obstacle: Obstacle) {
+		for cell in self.cells.values_mut() {
+			obstacle.collide(cell, &self.bounds);
+		}
+		self.obstacles.push(obstacle);
+	}
+
+	pub fn obstacles(&self) -> &[Obstacle] {
+		&self.obstacles
+	}
+
+	/// Updates cells on the given number of worker threads. If the workers can't be started, the
+	/// simulation keeps running on the calling thread.
+	pub fn set_threads(&mut self, threads: usize) -> Result<(), ThreadPoolBuildError> {
+		self.thread_pool = None;
+		if threads > 1 {
+			let pool = ThreadPoolBuilder::new()
+				.num_threads(threads)
+				.thread_name(|index| format!("sim-worker-{index}"))
+				.build()?;
+			self.thread_pool = Some(pool);
+		}
+		Ok(())
+	}
+
+	pub fn set_mutation_config(&mut self, config: MutationConfig) {
+		self.mutation = config;
+	}
+
+	pub fn set_nutrient_config(&mut self, config: NutrientConfig) {
+		self.nutrients = NutrientField::new(config, &self.bounds);
+	}
+
+	pub fn set_chemical_config(&mut self, config: ChemicalConfig) {
+		self.chemicals = ChemicalFields::new(config, &self.bounds);
+		self.mutation.chemical_channels = config.channels;
+	}
+
+	pub fn set_bond_config(&mut self, config: BondConfig) {
+		self.bonds.set_config(config);
+	}
+
+	pub fn add_cell(&mut self, genome: &Genome, position: Vec2) {
+		let entity = self.create_cell_entity(genome.build_receptors());
+		let id = self.rng.uuid();
+		let mut cell = Cell::new(id, entity);
+		cell.size = genome.size;
+		cell.color = genome.color;
 		cell.position = position;
-		self.cells.insert(Uuid::new_v4(), Mutex::new(cell));
+		cell.birth_tick = self.tick_count;
+		Self::confine(&self.bounds, &self.obstacles, &mut cell);
+		self.lineage.record_birth(&cell);
+		self.cells.insert(id, cell);
+		self.events
+			.emit(self.tick_count, EventKind::Birth { id, parent: None });
 	}
 
 	fn create_cell_entity(&mut self, receptors: Vec<Box<dyn Receptor>>) -> Entity {
-		let mut ecs_lock = self.ecs.lock().unwrap();
-		let entity = ecs_lock.entity();
-		ecs_lock.add_component(entity, Box::new(BaseReceptor::new()));
+		let entity = self.ecs.entity();
+		self.ecs
+			.add_component(entity, Box::new(BaseReceptor::new()));
 		for receptor in receptors {
-			ecs_lock.add_component(entity, receptor);
+			self.ecs.add_component(entity, receptor);
 		}
 		entity
 	}
 
-	fn kill_dead_cells(&mut self) {
-		let mut death_particles = Vec::<DeathParticles>::new();
-		let mut dead_ids = Vec::<Uuid>::new();
+	pub fn genome(&self, cell: &Cell) -> Genome {
+		let receptors = self
+			.ecs
+			.components(cell.entity)
+			.iter()
+			.filter_map(|receptor| receptor.gene())
+			.collect();
+		Genome::new(cell.color, cell.size, receptors)
+	}
+
+	fn divide_cells(&mut self) {
+		let dividing_ids: Vec<Uuid> = self
+			.cells
+			.iter()
+			.filter(|(_, cell)| cell.can_divide())
+			.map(|(id, _)| *id)
+			.collect();
+
+		for id in dividing_ids {
+			self.divide_cell(id);
+		}
+	}
+
+	fn divide_cell(&mut self, parent_id: Uuid) {
+		let Some(mut cell) = self.cells.remove(&parent_id) else {
+			return;
+		};
+		let mut parent = cell.clone();
+		parent.death_tick = Some(self.tick_count);
+		let direction = Vec2::from_angle(self.rng.rand() * TAU);
+		let sibling = cell.divide(direction);
+		let genome = self.genome(&cell);
+		self.ecs.remove_entity(cell.entity);
+		let partners = self.bonds.detach(parent_id);
+
+		let mut daughters = Vec::with_capacity(2);
+		for mut daughter in [cell, sibling] {
+			let mut daughter_genome = genome.clone();
+			daughter_genome.mutate(&mut self.rng, &self.mutation);
+			daughter.color = daughter_genome.color;
+			daughter.size = daughter_genome.size;
+			daughter.entity = self.create_cell_entity(daughter_genome.build_receptors());
+
+			Self::confine(&self.bounds, &self.obstacles, &mut daughter);
+
+			let id = self.rng.uuid();
+			daughter.id = id;
+			daughter.parent = Some(parent_id);
+			daughter.generation += 1;
+			daughter.birth_tick = self.tick_count;
+			daughters.push((id, daughter.position, daughter.size));
+			self.lineage.record_birth(&daughter);
+			self.cells.insert(id, daughter);
+			self.events.emit(
+				self.tick_count,
+				EventKind::Birth {
+					id,
+					parent: Some(parent_id)
+				}
+			);
+		}
+		if let [(first, ..), (second, ..)] = daughters[..] {
+			self.events.emit(
+				self.tick_count,
+				EventKind::Division {
+					parent: parent_id,
+					daughters: [first, second]
+				}
+			);
+		}
+		self.lineage.record_death(&parent);
 
-		for (id, cell) in &self.cells {
-			let cell_lock = cell.lock().unwrap();
-			if cell_lock.health > 0.0 {
+		// Adhesive cells stay attached to their sibling, and bonds of the parent are handed to
+		// whichever daughter ended up closer to the partner.
+		let adhesion = genome.adhesion();
+		if let [(first, _, first_size), (second, _, second_size)] = daughters[..] {
+			if adhesion > 0.0 {
+				let bond = Bond {
+					rest_length: first_size + second_size,
+					strength: adhesion
+				};
+				self.bonds.form(first, second, bond);
+			}
+		}
+		for (partner, bond) in partners {
+			let Some(partner) = self.cells.get(&partner) else {
 				continue;
+			};
+			let distance = |position: Vec2| {
+				self.bounds
+					.displacement(position, partner.position)
+					.length()
+			};
+			let closest = daughters
+				.iter()
+				.min_by(|a, b| distance(a.1).total_cmp(&distance(b.1)));
+			if let Some(&(id, _, size)) = closest {
+				let bond = Bond {
+					rest_length: size + partner.size,
+					..bond
+				};
+				self.bonds.form(id, partner.id, bond);
 			}
-			death_particles.push(DeathParticles::for_cell(&cell_lock));
-			dead_ids.push(*id);
 		}
+	}
 
-		self.kill_cells(&dead_ids);
-
-		for particles in death_particles {
-			self.spawn_death_particles(particles);
+	fn apply_effects(&mut self, effects: impl IntoIterator<Item = Effect>, dt: f64) {
+		for effect in effects {
+			match effect {
+				Effect::Predation {
+					predator,
+					prey,
+					strength
+				} => {
+					let Some(mut prey_cell) = self.cells.remove(&prey) else {
+						continue;
+					};
+					if let Some(predator_cell) = self.cells.get_mut(&predator) {
+						predation::apply_predation(predator_cell, &mut prey_cell, strength, dt);
+						self.events.emit(
+							self.tick_count,
+							EventKind::Predation {
+								predator,
+								prey,
+								strength
+							}
+						);
+					}
+					self.cells.insert(prey, prey_cell);
+				}
+				Effect::Emission {
+					channel,
+					position,
+					amount
+				} => self.chemicals.emit(channel, position, amount),
+				Effect::Adhesion {
+					cell,
+					other,
+					strength
+				} => {
+					let (Some(cell), Some(other)) = (self.cells.get(&cell), self.cells.get(&other))
+					else {
+						continue;
+					};
+					let bond = Bond {
+						rest_length: cell.size + other.size,
+						strength
+					};
+					self.bonds.form(cell.id, other.id, bond);
+				}
+			}
 		}
 	}
 
+	fn kill_dead_cells(&mut self) {
+		let dead_ids: Vec<Uuid> = self
+			.cells
+			.iter()
+			.filter(|(_, cell)| cell.health <= 0.0)
+			.map(|(id, _)| *id)
+			.collect();
+		self.kill_cells(&dead_ids);
+	}
+
 	fn kill_cells(&mut self, ids: &[Uuid]) {
 		for id in ids {
-			self.cells.remove(id);
+			if let Some(mut cell) = self.cells.remove(id) {
+				cell.death_tick = Some(self.tick_count);
+				self.ecs.remove_entity(cell.entity);
+				self.bonds.detach(*id);
+				self.lineage.record_death(&cell);
+				self.events.emit(
+					self.tick_count,
+					EventKind::Death {
+						id: *id,
+						position: cell.position,
+						color: cell.color,
+						size: cell.size
+					}
+				);
+			}
 		}
 	}
 
-	fn spawn_death_particles(
-		&self,
-		DeathParticles {
-			color,
-			spread,
-			position
-		}: DeathParticles
-	) {
-		let mut ps_lock = self.particle_system.lock().unwrap();
-		ps_lock.spawn_particle_group(GroupSpawnProps {
-			color,
-			count: 30,
-			position,
-			velocity: 50.0,
-			lifetime: 1.0,
-			spread,
-			size: 15.0,
-			opacity: 0.2
-		})
-	}
-
-	fn get_cells_without(&self, id: Uuid) -> Vec<&Mutex<Cell>> {
-		self.cells
+	fn confine(bounds: &WorldBounds, obstacles: &[Obstacle], cell: &mut Cell) {
+		for obstacle in obstacles {
+			obstacle.collide(cell, bounds);
+		}
+		bounds.constrain(cell);
+	}
+
+	fn build_grid(cells: &[Cell]) -> SpatialHash<usize> {
+		let mut grid = SpatialHash::new(GRID_CELL_SIZE);
+		for (index, cell) in cells.iter().enumerate() {
+			grid.insert(cell.position, index);
+		}
+		grid
+	}
+
+	fn build_tree(cells: &[Cell]) -> QuadTree {
+		let entries = cells
 			.iter()
-			.filter_map(|(other_id, other_cell)| {
-				if id == *other_id {
-					None
-				} else {
-					Some(other_cell)
-				}
+			.enumerate()
+			.map(|(index, cell)| TreeEntry {
+				index,
+				position: cell.position,
+				mass: cell.mass(),
+				color: cell.color
 			})
-			.collect()
+			.collect();
+		QuadTree::new(entries)
 	}
 }
 
+const GRID_CELL_SIZE: f32 = 250.0;
+
 impl Tick for Simulation {
 	fn tick(&mut self, dt: f64) {
-		for (id, cell) in &self.cells {
-			let mut cell_lock = cell.lock().unwrap();
-			let other_cells = self.get_cells_without(*id);
-			cell_lock.tick(&self.ecs, dt, &other_cells);
+		// Ticks are counted from one, so that events, lineage records and the statistics sample
+		// of a tick all carry its number, and cells added beforehand are stamped with zero.
+		self.tick_count += 1;
+
+		// Every cell reads the state of the previous tick from the snapshot and writes its new
+		// state back into `cells`, so the order cells are updated in doesn't affect the result.
+		self.snapshot.clear();
+		self.snapshot.extend(self.cells.values().cloned());
+
+		let grid = Self::build_grid(&self.snapshot);
+		let tree = match self.long_range_mode {
+			LongRangeMode::Exact => None,
+			LongRangeMode::BarnesHut { opening_angle } => {
+				Some((Self::build_tree(&self.snapshot), opening_angle))
+			}
+		};
+		let tree = tree
+			.as_ref()
+			.map(|(tree, opening_angle)| (tree, *opening_angle));
+		let max_size = self
+			.snapshot
+			.iter()
+			.fold(0.0, |max_size, cell| f32::max(max_size, cell.size));
+		let bond_forces = self.bonds.forces(&self.snapshot, &self.bounds);
+		let environment = Environment::new(&self.chemicals, self.rng.rand_u64());
+		let update_cell = |(index, cell): (usize, &mut Cell)| {
+			let neighbourhood = Neighbourhood::new(
+				&self.snapshot,
+				&grid,
+				tree,
+				&self.bounds,
+				&bond_forces,
+				max_size,
+				index
+			);
+			let energy = cell.energy;
+			let previous_position = cell.position;
+			let effects = cell.tick(&self.ecs, dt, &neighbourhood, &environment);
+			for obstacle in &self.obstacles {
+				obstacle.sweep(cell, previous_position, &self.bounds);
+			}
+			self.bounds.constrain(cell);
+			(effects, energy - cell.energy)
+		};
+
+		// Cells only ever write to themselves during an update, and anything they do to other
+		// cells is collected as effects and applied afterwards in a fixed order. Splitting them
+		// across workers therefore gives exactly the same result as updating them one by one.
+		let results: Vec<(Vec<Effect>, f64)> = match &self.thread_pool {
+			Some(pool) => {
+				let mut cells: Vec<&mut Cell> = self.cells.values_mut().collect();
+				pool.install(|| {
+					cells
+						.par_iter_mut()
+						.enumerate()
+						.map(|(index, cell)| update_cell((index, cell)))
+						.collect()
+				})
+			}
+			None => self
+				.cells
+				.values_mut()
+				.enumerate()
+				.map(update_cell)
+				.collect()
+		};
+		let mut effects = Vec::new();
+		for (id, (cell_effects, energy_spent)) in self.cells.keys().zip(results) {
+			effects.extend(cell_effects);
+			if energy_spent > 0.0 {
+				self.events.emit(
+					self.tick_count,
+					EventKind::EnergySpent {
+						id: *id,
+						amount: energy_spent
+					}
+				);
+			}
 		}
+		self.apply_effects(effects, dt);
+		self.bonds.break_strained(&self.cells, &self.bounds);
+		self.bonds.share_energy(&mut self.cells, dt);
+		self.nutrients.feed(self.cells.values_mut(), dt);
+		self.nutrients.tick(dt);
+		self.chemicals.tick(dt);
+		self.divide_cells();
 		self.kill_dead_cells();
+		self.stats.record(self.tick_count, self.cells.values());
+		self.events.flush();
 	}
 }
 
-impl ObjectProvider<layers::dots::Dot> for Simulation {
-	fn iter_objects(&self) -> Box<dyn Iterator<Item = layers::dots::Dot> + '_> {
-		let iter = self.cells.values().map(|cell| cell.into());
-		Box::new(iter)
+#[cfg(test)]
+mod tests {
+	use glam::Vec3;
+
+	use super::*;
+	use crate::sim::scenario::Scenario;
+
+	fn cell_states(sim: &Simulation) -> Vec<u8> {
+		bincode::serialize(&sim.state().cells).unwrap()
+	}
+
+	/// A simulation holding one cell of the given size and energy, whose daughters aren't mutated.
+	fn single_cell(size: f32, energy: f64) -> (Simulation, Uuid) {
+		let mut sim = Simulation::new(1);
+		sim.set_mutation_config(MutationConfig {
+			parameter_rate: 0.0,
+			color_jitter: 0.0,
+			size_jitter: 0.0,
+			insertion_rate: 0.0,
+			deletion_rate: 0.0,
+			..MutationConfig::default()
+		});
+		sim.add_cell(&Genome::new(Vec3::ONE, size, Vec::new()), Vec2::ZERO);
+		let id = *sim.cells.keys().next().unwrap();
+		sim.cells.get_mut(&id).unwrap().energy = energy;
+		(sim, id)
+	}
+
+	#[test]
+	fn division_needs_energy_and_size() {
+		for (size, energy) in [(10.0, 1.0), (2.0, 1000.0)] {
+			let (mut sim, id) = single_cell(size, energy);
+			sim.divide_cells();
+			assert_eq!(sim.cells.keys().collect::<Vec<_>>(), vec![&id]);
+		}
+	}
+
+	#[test]
+	fn division_splits_energy_and_mass_without_overlap() {
+		let (mut sim, parent) = single_cell(10.0, 100.0);
+		let mass = sim.cells[&parent].mass();
+		sim.divide_cells();
+
+		let daughters: Vec<&Cell> = sim.cells.values().collect();
+		assert_eq!(daughters.len(), 2);
+		assert!(!sim.cells.contains_key(&parent));
+		for daughter in &daughters {
+			assert_eq!(daughter.parent, Some(parent));
+			assert_eq!(daughter.generation, 1);
+			assert_eq!(daughter.energy, 50.0);
+			assert!((daughter.mass() - mass / 2.0).abs() < 1e-3 * mass);
+		}
+		let distance = daughters[0].position.distance(daughters[1].position);
+		assert!(distance >= daughters[0].size + daughters[1].size - 1e-3);
+	}
+
+	#[test]
+	fn thread_count_does_not_change_results() {
+		let scenario = Scenario::parse(include_str!("../../scenarios/soup.toml")).unwrap();
+		let run = |threads| {
+			let mut sim = scenario.build(7);
+			sim.set_threads(threads).unwrap();
+			for _ in 0..120 {
+				sim.tick(1.0 / 120.0);
+			}
+			sim
+		};
+		let single = run(1);
+		let parallel = run(4);
+		assert!(single.cells().count() > 0);
+		assert_eq!(cell_states(&single), cell_states(&parallel));
 	}
 }
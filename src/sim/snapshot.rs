@@ -0,0 +1,320 @@
+use std::{
+	fmt,
+	fs::File,
+	io::{self, BufReader, BufWriter, Read, Write},
+	path::Path
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{particles::ParticleSystem, rand::Rng};
+
+use super::{
+	bonds::{Bond, BondConfig},
+	bounds::WorldBounds,
+	cell::Cell,
+	chemicals::ChemicalFields,
+	genome::{MutationConfig, ReceptorGene},
+	lineage::Lineage,
+	nutrients::NutrientField,
+	obstacles::Obstacle,
+	stats::{Sample, StatsConfig},
+	LongRangeMode, Simulation
+};
+
+/// Bumped whenever the serialized layout of a snapshot changes, so that older snapshots are
+/// rejected up front instead of failing to deserialize or being read wrongly.
+pub const SNAPSHOT_VERSION: u32 = 6;
+
+const MAGIC: &[u8; 8] = b"CELLSNAP";
+
+/// A cell together with the genes its receptors were built from. The receptors themselves hold no
+/// state beyond their gene, so rebuilding them from it restores the cell's entity exactly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellState {
+	pub cell: Cell,
+	pub receptors: Vec<ReceptorGene>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationState {
+	pub rng: Rng,
+	pub particle_seed: u64,
+	pub tick_count: u64,
+	pub long_range_mode: LongRangeMode,
+	pub bounds: WorldBounds,
+	pub obstacles: Vec<Obstacle>,
+	pub mutation: MutationConfig,
+	pub nutrients: NutrientField,
+	pub chemicals: ChemicalFields,
+	pub bond_config: BondConfig,
+	pub bonds: Vec<(Uuid, Uuid, Bond)>,
+	pub cells: Vec<CellState>,
+	pub lineage: Lineage,
+	pub stats_config: StatsConfig,
+	pub stats_samples: Vec<Sample>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+	Binary,
+	Json
+}
+
+impl SnapshotFormat {
+	pub fn from_path(path: &Path) -> Self {
+		match path.extension().and_then(|extension| extension.to_str()) {
+			Some("json") => Self::Json,
+			_ => Self::Binary
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+	Io(io::Error),
+	Binary(bincode::Error),
+	Json(serde_json::Error),
+	UnsupportedVersion(u32)
+}
+
+impl fmt::Display for SnapshotError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Io(err) => write!(f, "{err}"),
+			Self::Binary(err) => write!(f, "Invalid snapshot: {err}"),
+			Self::Json(err) => write!(f, "Invalid snapshot: {err}"),
+			Self::UnsupportedVersion(version) => write!(
+				f,
+				"Unsupported snapshot version {version}, expected {SNAPSHOT_VERSION}"
+			)
+		}
+	}
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+	fn from(value: io::Error) -> Self {
+		Self::Io(value)
+	}
+}
+
+impl From<bincode::Error> for SnapshotError {
+	fn from(value: bincode::Error) -> Self {
+		Self::Binary(value)
+	}
+}
+
+impl From<serde_json::Error> for SnapshotError {
+	fn from(value: serde_json::Error) -> Self {
+		Self::Json(value)
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+	pub simulation: SimulationState,
+	pub particles: Option<ParticleSystem>
+}
+
+#[derive(Serialize)]
+struct VersionedSnapshot<'a> {
+	version: u32,
+	#[serde(flatten)]
+	snapshot: &'a Snapshot
+}
+
+#[derive(Deserialize)]
+struct VersionHeader {
+	version: u32
+}
+
+impl Snapshot {
+	pub fn capture(simulation: &Simulation, particles: Option<&ParticleSystem>) -> Self {
+		Self {
+			simulation: simulation.state(),
+			particles: particles.cloned()
+		}
+	}
+
+	pub fn restore(self) -> (Simulation, Option<ParticleSystem>) {
+		(Simulation::from_state(self.simulation), self.particles)
+	}
+
+	/// Binary snapshots start with a magic number and the format version, followed by the
+	/// bincode-encoded snapshot. JSON snapshots carry the version as a top-level field.
+	pub fn write<W: Write>(
+		&self,
+		format: SnapshotFormat,
+		mut output: W
+	) -> Result<(), SnapshotError> {
+		match format {
+			SnapshotFormat::Binary => {
+				output.write_all(MAGIC)?;
+				output.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+				bincode::serialize_into(&mut output, self)?;
+			}
+			SnapshotFormat::Json => {
+				let versioned = VersionedSnapshot {
+					version: SNAPSHOT_VERSION,
+					snapshot: self
+				};
+				serde_json::to_writer(&mut output, &versioned)?;
+			}
+		}
+		output.flush()?;
+		Ok(())
+	}
+
+	pub fn read<R: Read>(mut input: R) -> Result<Self, SnapshotError> {
+		let mut data = Vec::new();
+		input.read_to_end(&mut data)?;
+		if let Some(data) = data.strip_prefix(MAGIC) {
+			let (version, body) = data.split_at(data.len().min(4));
+			let version = u32::from_le_bytes(version.try_into().map_err(|_| {
+				io::Error::new(io::ErrorKind::UnexpectedEof, "Snapshot header is truncated")
+			})?);
+			if version != SNAPSHOT_VERSION {
+				return Err(SnapshotError::UnsupportedVersion(version));
+			}
+			return Ok(bincode::deserialize(body)?);
+		}
+		let VersionHeader { version } = serde_json::from_slice(&data)?;
+		if version != SNAPSHOT_VERSION {
+			return Err(SnapshotError::UnsupportedVersion(version));
+		}
+		Ok(serde_json::from_slice(&data)?)
+	}
+
+	pub fn save(&self, path: &Path) -> Result<(), SnapshotError> {
+		let file = File::create(path)?;
+		self.write(SnapshotFormat::from_path(path), BufWriter::new(file))
+	}
+
+	pub fn load(path: &Path) -> Result<Self, SnapshotError> {
+		let file = File::open(path)?;
+		Self::read(BufReader::new(file))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glam::{Vec2, Vec3};
+
+	use super::*;
+	use crate::{
+		particles::{DeathParticles, GroupSpawnProps},
+		sim::{scenario::Scenario, Tick}
+	};
+
+	const DT: f64 = 1.0 / 120.0;
+
+	struct Run {
+		simulation: Simulation,
+		particles: ParticleSystem,
+		deaths: DeathParticles
+	}
+
+	impl Run {
+		fn new(mut simulation: Simulation, particles: ParticleSystem) -> Self {
+			let deaths = DeathParticles::new(&mut simulation);
+			Self {
+				simulation,
+				particles,
+				deaths
+			}
+		}
+
+		fn restore(snapshot: Snapshot) -> Self {
+			let (simulation, particles) = snapshot.restore();
+			Self::new(simulation, particles.unwrap())
+		}
+
+		fn advance(&mut self, ticks: usize) {
+			for _ in 0..ticks {
+				self.simulation.tick(DT);
+				self.deaths.spawn(&mut self.particles);
+				self.particles.tick(DT);
+			}
+			self.particles.spawn_particle_group(GroupSpawnProps {
+				color: Vec3::ONE,
+				count: 5,
+				position: Vec2::ZERO,
+				velocity: 10.0,
+				lifetime: 1.0,
+				spread: 5.0,
+				size: 2.0,
+				opacity: 1.0
+			});
+		}
+	}
+
+	fn assert_same(expected: &Run, actual: &Run) {
+		let mut expected_state = expected.simulation.state();
+		let mut actual_state = actual.simulation.state();
+		assert_eq!(expected_state.cells.len(), actual_state.cells.len());
+		for (expected, actual) in expected_state.cells.iter().zip(&mut actual_state.cells) {
+			// Entities are handed out again on restore, so only their numbering may differ.
+			actual.cell.entity = expected.cell.entity;
+			assert_eq!(
+				bincode::serialize(expected).unwrap(),
+				bincode::serialize(actual).unwrap()
+			);
+		}
+		expected_state.cells.clear();
+		actual_state.cells.clear();
+		assert_eq!(
+			bincode::serialize(&expected_state).unwrap(),
+			bincode::serialize(&actual_state).unwrap()
+		);
+		assert_eq!(
+			bincode::serialize(&expected.particles).unwrap(),
+			bincode::serialize(&actual.particles).unwrap()
+		);
+	}
+
+	#[test]
+	fn restored_snapshots_continue_exactly() {
+		let scenario = Scenario::parse(include_str!("../../scenarios/soup.toml")).unwrap();
+		let simulation = scenario.build(3);
+		let particles = ParticleSystem::new(simulation.particle_seed());
+		let mut original = Run::new(simulation, particles);
+		original.advance(60);
+
+		let snapshot = Snapshot::capture(&original.simulation, Some(&original.particles));
+		let mut encoded = Vec::new();
+		for format in [SnapshotFormat::Binary, SnapshotFormat::Json] {
+			let mut data = Vec::new();
+			snapshot.write(format, &mut data).unwrap();
+			encoded.push(data);
+		}
+
+		original.advance(60);
+		for data in encoded {
+			let mut restored = Run::restore(Snapshot::read(data.as_slice()).unwrap());
+			restored.advance(60);
+			assert_same(&original, &restored);
+		}
+	}
+
+	#[test]
+	fn other_versions_are_rejected() {
+		// Version 3 snapshots have the same header but no particle seed, version 4 snapshots have
+		// no death tick on their cells and version 5 snapshots lay out field settings flat.
+		for version in [1, 3, 4, 5, SNAPSHOT_VERSION + 1] {
+			let mut data = MAGIC.to_vec();
+			data.extend(version.to_le_bytes());
+			assert!(matches!(
+				Snapshot::read(data.as_slice()),
+				Err(SnapshotError::UnsupportedVersion(found)) if found == version
+			));
+			let json = format!(r#"{{"version": {version}}}"#);
+			assert!(matches!(
+				Snapshot::read(json.as_bytes()),
+				Err(SnapshotError::UnsupportedVersion(found)) if found == version
+			));
+		}
+	}
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+use glam::{IVec2, Vec2};
+
+use super::{
+	bounds::WorldBounds,
+	cell::Cell,
+	quadtree::{Cluster, QuadTree, TreeItem},
+	receptors::{Reach, Receptor}
+};
+
+#[derive(Debug)]
+struct Bucket<T> {
+	key: IVec2,
+	entries: Vec<(Vec2, T)>
+}
+
+#[derive(Debug)]
+pub struct SpatialHash<T> {
+	cell_size: f32,
+	bucket_indices: HashMap<IVec2, usize>,
+	buckets: Vec<Bucket<T>>
+}
+
+impl<T: Copy> SpatialHash<T> {
+	pub fn new(cell_size: f32) -> Self {
+		assert!(cell_size > 0.0, "Spatial hash cell size must be positive");
+		Self {
+			cell_size,
+			bucket_indices: HashMap::new(),
+			buckets: Vec::new()
+		}
+	}
+
+	pub fn insert(&mut self, position: Vec2, item: T) {
+		let key = self.key(position);
+		let index = *self.bucket_indices.entry(key).or_insert_with(|| {
+			self.buckets.push(Bucket {
+				key,
+				entries: Vec::new()
+			});
+			self.buckets.len() - 1
+		});
+		self.buckets[index].entries.push((position, item));
+	}
+
+	pub fn query(&self, position: Vec2, radius: f32) -> impl Iterator<Item = T> + '_ {
+		let min = self.key(position - Vec2::splat(radius));
+		let max = self.key(position + Vec2::splat(radius));
+		let num_keys = (max.x as i64 - min.x as i64 + 1) * (max.y as i64 - min.y as i64 + 1);
+
+		// Wide queries would visit mostly empty buckets, so walk the occupied ones instead.
+		let buckets: Box<dyn Iterator<Item = &Bucket<T>>> =
+			if num_keys > self.buckets.len() as i64 {
+				Box::new(self.buckets.iter().filter(move |bucket| {
+					bucket.key.cmpge(min).all() && bucket.key.cmple(max).all()
+				}))
+			} else {
+				Box::new(
+					(min.y..=max.y)
+						.flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+						.filter_map(|key| self.bucket_indices.get(&key))
+						.map(|&index| &self.buckets[index])
+				)
+			};
+
+		let radius_squared = radius * radius;
+		buckets
+			.flat_map(|bucket| bucket.entries.iter())
+			.filter(move |(entry_position, _)| {
+				entry_position.distance_squared(position) <= radius_squared
+			})
+			.map(|(_, item)| *item)
+	}
+
+	fn key(&self, position: Vec2) -> IVec2 {
+		(position / self.cell_size).floor().as_ivec2()
+	}
+}
+
+pub enum Interaction<'a> {
+	Cell(&'a Cell),
+	Cluster(Cluster)
+}
+
+pub struct Neighbourhood<'a> {
+	cells: &'a [Cell],
+	grid: &'a SpatialHash<usize>,
+	tree: Option<(&'a QuadTree, f32)>,
+	bounds: &'a WorldBounds,
+	bond_forces: &'a [Vec2],
+	max_size: f32,
+	own_index: usize
+}
+
+impl<'a> Neighbourhood<'a> {
+	pub fn new(
+		cells: &'a [Cell],
+		grid: &'a SpatialHash<usize>,
+		tree: Option<(&'a QuadTree, f32)>,
+		bounds: &'a WorldBounds,
+		bond_forces: &'a [Vec2],
+		max_size: f32,
+		own_index: usize
+	) -> Self {
+		Self {
+			cells,
+			grid,
+			tree,
+			bounds,
+			bond_forces,
+			max_size,
+			own_index
+		}
+	}
+
+	pub fn bond_force(&self) -> Vec2 {
+		self.bond_forces
+			.get(self.own_index)
+			.copied()
+			.unwrap_or(Vec2::ZERO)
+	}
+
+	pub fn for_each_interaction<F: FnMut(Interaction)>(
+		&self,
+		cell: &Cell,
+		receptor: &dyn Receptor,
+		mut func: F
+	) {
+		let position = cell.position;
+		let range = match receptor.reach() {
+			Reach::None => return,
+			Reach::Unlimited => None,
+			Reach::Radius(radius) => Some(radius),
+			Reach::Contact => Some(cell.size + self.max_size)
+		};
+
+		let Some(offsets) = self.bounds.image_offsets(position, range) else {
+			for other_cell in self.all() {
+				let image_position =
+					position + self.bounds.displacement(position, other_cell.position);
+				if range.is_some_and(|range| image_position.distance(position) > range) {
+					continue;
+				}
+				Self::visit_image(other_cell, other_cell.position - image_position, &mut func);
+			}
+			return;
+		};
+
+		for offset in offsets {
+			self.for_each_near(position + offset, offset, range, receptor, &mut func);
+		}
+	}
+
+	fn for_each_near<F: FnMut(Interaction)>(
+		&self,
+		position: Vec2,
+		offset: Vec2,
+		range: Option<f32>,
+		receptor: &dyn Receptor,
+		func: &mut F
+	) {
+		match (receptor.long_range(), self.tree) {
+			(true, Some((tree, opening_angle))) => tree.visit(
+				position,
+				range,
+				opening_angle,
+				self.own_index,
+				&mut |item| match item {
+					TreeItem::Single(index) => Self::visit_image(&self.cells[index], offset, func),
+					TreeItem::Cluster(mut cluster) => {
+						cluster.center_of_mass -= offset;
+						func(Interaction::Cluster(cluster))
+					}
+				}
+			),
+			_ => match range {
+				Some(range) => self
+					.within(position, range)
+					.for_each(|cell| Self::visit_image(cell, offset, func)),
+				None => self.all().for_each(|cell| func(Interaction::Cell(cell)))
+			}
+		}
+	}
+
+	/// Cells seen across the edge of a periodic world are passed on as a copy moved next to the
+	/// querying cell, so receptors never have to know about the world's topology.
+	fn visit_image<F: FnMut(Interaction)>(cell: &Cell, offset: Vec2, func: &mut F) {
+		if offset == Vec2::ZERO {
+			func(Interaction::Cell(cell));
+			return;
+		}
+		let mut image = cell.clone();
+		image.position -= offset;
+		func(Interaction::Cell(&image));
+	}
+
+	fn all(&self) -> impl Iterator<Item = &'a Cell> + '_ {
+		self.cells
+			.iter()
+			.enumerate()
+			.filter(|(index, _)| *index != self.own_index)
+			.map(|(_, cell)| cell)
+	}
+
+	fn within(&self, position: Vec2, radius: f32) -> impl Iterator<Item = &'a Cell> + '_ {
+		self.grid
+			.query(position, radius)
+			.filter(|index| *index != self.own_index)
+			.map(|index| &self.cells[index])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		rand::Rng,
+		sim::{genome::ReceptorGene, receptors::InteractionAccumulator}
+	};
+
+	#[derive(Debug)]
+	struct Probe(Reach);
+
+	impl Receptor for Probe {
+		fn interaction_accumulator<'a>(&'a self) -> Box<dyn InteractionAccumulator + 'a> {
+			unimplemented!("Probes only report their reach")
+		}
+
+		fn reach(&self) -> Reach {
+			self.0
+		}
+
+		fn gene(&self) -> Option<ReceptorGene> {
+			None
+		}
+	}
+
+	fn random_cells(count: usize, extent: f32) -> Vec<Cell> {
+		let mut rng = Rng::new(9);
+		(0..count)
+			.map(|index| {
+				let position = Vec2::new(
+					rng.rand_in_range(-extent, extent),
+					rng.rand_in_range(-extent, extent)
+				);
+				Cell::test_cell(index as u128, 1.0, position)
+			})
+			.collect()
+	}
+
+	fn grid(cells: &[Cell]) -> SpatialHash<usize> {
+		let mut grid = SpatialHash::new(20.0);
+		for (index, cell) in cells.iter().enumerate() {
+			grid.insert(cell.position, index);
+		}
+		grid
+	}
+
+	/// Every neighbour reported, as its index and the position it was seen at.
+	fn neighbours(
+		cells: &[Cell],
+		grid: &SpatialHash<usize>,
+		bounds: &WorldBounds,
+		reach: Reach,
+		own_index: usize
+	) -> Vec<(u128, Vec2)> {
+		let neighbourhood = Neighbourhood::new(cells, grid, None, bounds, &[], 0.0, own_index);
+		let mut found = Vec::new();
+		neighbourhood.for_each_interaction(&cells[own_index], &Probe(reach), |interaction| {
+			if let Interaction::Cell(cell) = interaction {
+				found.push((cell.id.as_u128(), cell.position));
+			}
+		});
+		found.sort_by_key(|(id, _)| *id);
+		found
+	}
+
+	fn brute_force(
+		cells: &[Cell],
+		bounds: &WorldBounds,
+		radius: f32,
+		own_index: usize
+	) -> Vec<(u128, Vec2)> {
+		let position = cells[own_index].position;
+		cells
+			.iter()
+			.enumerate()
+			.filter(|(index, _)| *index != own_index)
+			.map(|(_, cell)| {
+				let image = position + bounds.displacement(position, cell.position);
+				(cell.id.as_u128(), image)
+			})
+			.filter(|(_, image)| image.distance(position) <= radius)
+			.collect()
+	}
+
+	fn assert_same_neighbours(found: &[(u128, Vec2)], expected: &[(u128, Vec2)]) {
+		let ids = |list: &[(u128, Vec2)]| list.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+		assert_eq!(ids(found), ids(expected));
+		for ((_, found), (_, expected)) in found.iter().zip(expected) {
+			assert!(found.distance(*expected) < 1e-3, "{found} != {expected}");
+		}
+	}
+
+	#[test]
+	fn query_matches_brute_force() {
+		let cells = random_cells(400, 200.0);
+		let grid = grid(&cells);
+		// The wide query is answered by walking the occupied buckets instead of the key range.
+		for radius in [5.0, 35.0, 1000.0] {
+			for center in [Vec2::ZERO, Vec2::new(150.0, -80.0)] {
+				let mut found: Vec<usize> = grid.query(center, radius).collect();
+				found.sort_unstable();
+				let expected: Vec<usize> = (0..cells.len())
+					.filter(|&index| cells[index].position.distance(center) <= radius)
+					.collect();
+				assert_eq!(found, expected, "radius {radius} around {center}");
+			}
+		}
+	}
+
+	#[test]
+	fn neighbourhood_matches_brute_force() {
+		let cells = random_cells(300, 200.0);
+		let grid = grid(&cells);
+		let all_bounds = [
+			WorldBounds::Open,
+			WorldBounds::Torus {
+				size: Vec2::splat(400.0)
+			}
+		];
+		for bounds in &all_bounds {
+			// The largest radius covers more than half the torus, so every cell is checked
+			// directly instead of through the grid.
+			for radius in [30.0, 90.0, 250.0] {
+				for own_index in [0, 42, 299] {
+					let found = neighbours(&cells, &grid, bounds, Reach::Radius(radius), own_index);
+					let expected = brute_force(&cells, bounds, radius, own_index);
+					assert_same_neighbours(&found, &expected);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn unlimited_reach_sees_every_other_cell() {
+		let cells = random_cells(50, 200.0);
+		let grid = grid(&cells);
+		let found = neighbours(&cells, &grid, &WorldBounds::Open, Reach::Unlimited, 7);
+		let expected = brute_force(&cells, &WorldBounds::Open, f32::INFINITY, 7);
+		assert_same_neighbours(&found, &expected);
+	}
+}
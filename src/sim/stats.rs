@@ -0,0 +1,349 @@
+use std::{
+	collections::VecDeque,
+	io::{self, Write}
+};
+
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use super::cell::Cell;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsConfig {
+	pub interval: u64,
+	pub history: usize
+}
+
+impl Default for StatsConfig {
+	fn default() -> Self {
+		Self {
+			interval: 60,
+			history: 1000
+		}
+	}
+}
+
+pub const COLOR_CLUSTERS: [(&str, Vec3); 8] = [
+	("black", Vec3::new(0.0, 0.0, 0.0)),
+	("red", Vec3::new(1.0, 0.0, 0.0)),
+	("green", Vec3::new(0.0, 1.0, 0.0)),
+	("blue", Vec3::new(0.0, 0.0, 1.0)),
+	("yellow", Vec3::new(1.0, 1.0, 0.0)),
+	("cyan", Vec3::new(0.0, 1.0, 1.0)),
+	("magenta", Vec3::new(1.0, 0.0, 1.0)),
+	("white", Vec3::new(1.0, 1.0, 1.0))
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+	pub tick: u64,
+	pub population: usize,
+	pub total_energy: f64,
+	pub mean_energy: f64,
+	pub total_health: f32,
+	pub mean_health: f32,
+	pub mean_speed: f32,
+	pub total_mass: f32,
+	pub color_counts: [usize; COLOR_CLUSTERS.len()]
+}
+
+impl Sample {
+	pub fn collect<'a>(tick: u64, cells: impl Iterator<Item = &'a Cell>) -> Self {
+		let mut sample = Self {
+			tick,
+			population: 0,
+			total_energy: 0.0,
+			mean_energy: 0.0,
+			total_health: 0.0,
+			mean_health: 0.0,
+			mean_speed: 0.0,
+			total_mass: 0.0,
+			color_counts: [0; COLOR_CLUSTERS.len()]
+		};
+		let mut total_speed = 0.0;
+		for cell in cells {
+			sample.population += 1;
+			sample.total_energy += cell.energy;
+			sample.total_health += cell.health;
+			sample.total_mass += cell.mass();
+			total_speed += cell.velocity.length();
+			sample.color_counts[color_cluster(cell.color)] += 1;
+		}
+		if sample.population > 0 {
+			sample.mean_energy = sample.total_energy / sample.population as f64;
+			sample.mean_health = sample.total_health / sample.population as f32;
+			sample.mean_speed = total_speed / sample.population as f32;
+		}
+		sample
+	}
+}
+
+fn color_cluster(color: Vec3) -> usize {
+	COLOR_CLUSTERS
+		.iter()
+		.enumerate()
+		.min_by(|(_, (_, a)), (_, (_, b))| {
+			color
+				.distance_squared(*a)
+				.total_cmp(&color.distance_squared(*b))
+		})
+		.map(|(index, _)| index)
+		.unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+	Csv,
+	Jsonl
+}
+
+pub struct StatsWriter {
+	format: StatsFormat,
+	output: Box<dyn Write + Send>,
+	started: bool
+}
+
+impl StatsWriter {
+	pub fn new(format: StatsFormat, output: Box<dyn Write + Send>) -> Self {
+		Self {
+			format,
+			output,
+			started: false
+		}
+	}
+
+	pub fn write(&mut self, sample: &Sample) -> io::Result<()> {
+		match self.format {
+			StatsFormat::Csv => self.write_csv(sample)?,
+			StatsFormat::Jsonl => {
+				serde_json::to_writer(&mut self.output, sample)?;
+				writeln!(self.output)?;
+			}
+		}
+		self.output.flush()
+	}
+
+	fn write_csv(&mut self, sample: &Sample) -> io::Result<()> {
+		if !self.started {
+			write!(
+				self.output,
+				"tick,population,total_energy,mean_energy,total_health,mean_health,mean_speed,\
+				 total_mass"
+			)?;
+			for (name, _) in COLOR_CLUSTERS {
+				write!(self.output, ",{name}")?;
+			}
+			writeln!(self.output)?;
+			self.started = true;
+		}
+		write!(
+			self.output,
+			"{},{},{},{},{},{},{},{}",
+			sample.tick,
+			sample.population,
+			sample.total_energy,
+			sample.mean_energy,
+			sample.total_health,
+			sample.mean_health,
+			sample.mean_speed,
+			sample.total_mass
+		)?;
+		for count in sample.color_counts {
+			write!(self.output, ",{count}")?;
+		}
+		writeln!(self.output)
+	}
+}
+
+pub struct StatsCollector {
+	config: StatsConfig,
+	samples: VecDeque<Sample>,
+	writers: Vec<StatsWriter>
+}
+
+impl StatsCollector {
+	pub fn new(config: StatsConfig) -> Self {
+		Self {
+			config,
+			samples: VecDeque::with_capacity(config.history),
+			writers: Vec::new()
+		}
+	}
+
+	pub fn config(&self) -> StatsConfig {
+		self.config
+	}
+
+	pub fn set_config(&mut self, config: StatsConfig) {
+		self.config = config;
+		while self.samples.len() > config.history {
+			self.samples.pop_front();
+		}
+	}
+
+	pub fn add_writer(&mut self, writer: StatsWriter) {
+		self.writers.push(writer);
+	}
+
+	pub fn samples(&self) -> impl Iterator<Item = &Sample> {
+		self.samples.iter()
+	}
+
+	pub fn restore_samples(&mut self, samples: impl IntoIterator<Item = Sample>) {
+		self.samples.clear();
+		self.samples.extend(samples);
+		while self.samples.len() > self.config.history {
+			self.samples.pop_front();
+		}
+	}
+
+	pub fn latest(&self) -> Option<&Sample> {
+		self.samples.back()
+	}
+
+	pub fn record<'a>(&mut self, tick: u64, cells: impl Iterator<Item = &'a Cell>) {
+		if self.config.interval == 0 || !tick.is_multiple_of(self.config.interval) {
+			return;
+		}
+		let sample = Sample::collect(tick, cells);
+		self.writers
+			.retain_mut(|writer| match writer.write(&sample) {
+				Ok(()) => true,
+				Err(err) => {
+					eprintln!("Failed to write statistics, closing writer: {err}");
+					false
+				}
+			});
+		if self.config.history == 0 {
+			return;
+		}
+		if self.samples.len() == self.config.history {
+			self.samples.pop_front();
+		}
+		self.samples.push_back(sample);
+	}
+}
+
+impl std::fmt::Debug for StatsCollector {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("StatsCollector")
+			.field("config", &self.config)
+			.field("samples", &self.samples.len())
+			.field("writers", &self.writers.len())
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, Mutex};
+
+	use glam::Vec2;
+
+	use super::*;
+
+	/// Output that can still be read after it has been handed to a writer.
+	#[derive(Clone, Default)]
+	struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for SharedOutput {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	impl SharedOutput {
+		fn contents(&self) -> String {
+			String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+		}
+	}
+
+	fn cells() -> Vec<Cell> {
+		let mut cells = vec![
+			Cell::test_cell(0, 5.0, Vec2::ZERO),
+			Cell::test_cell(1, 3.0, Vec2::new(20.0, 0.0)),
+		];
+		cells[0].color = Vec3::new(0.9, 0.1, 0.0);
+		cells[1].velocity = Vec2::new(3.0, 4.0);
+		cells
+	}
+
+	#[test]
+	fn history_keeps_the_latest_samples() {
+		let mut stats = StatsCollector::new(StatsConfig {
+			interval: 2,
+			history: 3
+		});
+		for tick in 0..12 {
+			stats.record(tick, cells().iter());
+		}
+		let ticks: Vec<u64> = stats.samples().map(|sample| sample.tick).collect();
+		assert_eq!(ticks, vec![6, 8, 10]);
+		assert_eq!(stats.latest().unwrap().population, 2);
+
+		stats.set_config(StatsConfig {
+			interval: 2,
+			history: 1
+		});
+		let ticks: Vec<u64> = stats.samples().map(|sample| sample.tick).collect();
+		assert_eq!(ticks, vec![10]);
+	}
+
+	#[test]
+	fn csv_columns_match_jsonl_fields() {
+		let csv = SharedOutput::default();
+		let jsonl = SharedOutput::default();
+		let mut stats = StatsCollector::new(StatsConfig {
+			interval: 1,
+			history: 0
+		});
+		stats.add_writer(StatsWriter::new(StatsFormat::Csv, Box::new(csv.clone())));
+		stats.add_writer(StatsWriter::new(
+			StatsFormat::Jsonl,
+			Box::new(jsonl.clone())
+		));
+		for tick in 0..2 {
+			stats.record(tick, cells().iter());
+		}
+		assert_eq!(stats.samples().count(), 0);
+
+		let csv = csv.contents();
+		let mut lines = csv.lines();
+		let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+		let rows: Vec<&str> = lines.collect();
+		let jsonl = jsonl.contents();
+		let objects: Vec<serde_json::Value> = jsonl
+			.lines()
+			.map(|line| serde_json::from_str(line).unwrap())
+			.collect();
+		assert_eq!(rows.len(), 2);
+		assert_eq!(objects.len(), 2);
+
+		let fields = objects[0].as_object().unwrap();
+		assert_eq!(header.len(), fields.len() - 1 + COLOR_CLUSTERS.len());
+		for (row, object) in rows.iter().zip(&objects) {
+			let values: Vec<f64> = row.split(',').map(|value| value.parse().unwrap()).collect();
+			assert_eq!(values.len(), header.len());
+			for (column, value) in header.iter().zip(values) {
+				let expected = match COLOR_CLUSTERS.iter().position(|(name, _)| name == column) {
+					Some(index) => &object["color_counts"][index],
+					None => &object[*column]
+				};
+				let expected = expected
+					.as_f64()
+					.unwrap_or_else(|| panic!("no field {column}"));
+				assert!(
+					(value - expected).abs() < 1e-6,
+					"{column}: {value} != {expected}"
+				);
+			}
+		}
+		assert_eq!(objects[1]["tick"], 1);
+		assert_eq!(objects[1]["color_counts"][1], 1);
+	}
+}
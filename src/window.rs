@@ -12,7 +12,7 @@ use glutin_winit::{DisplayBuilder, GlWindow};
 use raw_window_handle::HasRawWindowHandle;
 use winit::{
 	dpi::{LogicalSize, PhysicalSize},
-	event::{Event, WindowEvent},
+	event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
 	event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy},
 	window::{Theme, WindowBuilder}
 };
@@ -26,12 +26,15 @@ const MIN_HEIGHT: u32 = 240;
 
 pub struct Window {
 	window: winit::window::Window,
+	title: String,
 	event_loop: Option<EventLoop<f64>>,
 	size: LogicalSize<f32>,
 	gl: Rc<glow::Context>,
 	gl_surface: Surface<WindowSurface>,
 	gl_context: PossiblyCurrentContext,
-	renderer: Option<Box<dyn Renderer>>
+	renderer: Option<Box<dyn Renderer>>,
+	close_handler: Option<Box<dyn FnOnce()>>,
+	key_handler: Option<Box<dyn FnMut(VirtualKeyCode) -> Option<String>>>
 }
 
 impl Window {
@@ -51,15 +54,30 @@ impl Window {
 
 		Self {
 			window,
+			title: title.to_owned(),
 			size,
 			renderer: Some(renderer),
 			event_loop: Some(event_loop),
 			gl,
 			gl_surface,
-			gl_context
+			gl_context,
+			close_handler: None,
+			key_handler: None
 		}
 	}
 
+	pub fn on_close<F: FnOnce() + 'static>(&mut self, handler: F) {
+		self.close_handler = Some(Box::new(handler));
+	}
+
+	/// The handler may return a status, which is shown in the title bar after the window's title.
+	pub fn on_key_press<F: FnMut(VirtualKeyCode) -> Option<String> + 'static>(
+		&mut self,
+		handler: F
+	) {
+		self.key_handler = Some(Box::new(handler));
+	}
+
 	pub fn proxy(&self) -> EventLoopProxy<f64> {
 		self.event_loop
 			.as_ref()
@@ -102,8 +120,25 @@ impl Window {
 	fn handle_window_event(&mut self, event: WindowEvent, control_flow: &mut ControlFlow) {
 		match event {
 			WindowEvent::Resized(size) => self.resize(size),
+			WindowEvent::KeyboardInput {
+				input:
+					KeyboardInput {
+						state: ElementState::Pressed,
+						virtual_keycode: Some(key),
+						..
+					},
+				..
+			} => {
+				let status = self.key_handler.as_mut().and_then(|handler| handler(key));
+				if let Some(status) = status {
+					self.window.set_title(&format!("{} - {status}", self.title));
+				}
+			}
 			WindowEvent::CloseRequested => {
 				control_flow.set_exit();
+				if let Some(handler) = self.close_handler.take() {
+					handler();
+				}
 				if let Some(renderer) = self.renderer.take() {
 					mem::drop(renderer); // Ensure the renderer is dropped before the OpenGL context is destroyed
 				}